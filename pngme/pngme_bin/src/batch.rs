@@ -0,0 +1,150 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use anyhow::{Error, Result};
+use clap::Args;
+use rayon::prelude::*;
+use walkdir::WalkDir;
+
+// 批量处理多个文件的参数
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    // 把路径作为目录，递归处理其中所有 .png 文件
+    #[clap(short = 'r', long = "recursive")]
+    pub recursive: bool,
+    // 并行处理的线程数，0 或未指定时等于 CPU 核数
+    #[clap(short = 'j', long = "jobs")]
+    pub jobs: Option<usize>,
+}
+
+impl BatchArgs {
+    // 路径是否代表多个输入文件
+    pub fn is_batch(&self, path: &Path) -> bool {
+        self.recursive || is_pattern(path)
+    }
+}
+
+// 路径中含有通配符且不是已存在的文件时按 glob 模式展开
+fn is_pattern(path: &Path) -> bool {
+    let text = path.to_string_lossy();
+    text.contains(['*', '?', '[']) && !path.exists()
+}
+
+fn has_png_extension(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+}
+
+// 展开 glob 模式并在 recursive 时遍历目录，按路径排序返回所有输入文件
+// 目录中只收集扩展名为 .png 的文件；模式直接匹配到的文件不检查扩展名
+pub fn collect_inputs(path: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let roots: Vec<PathBuf> = if is_pattern(path) {
+        glob::glob(&path.to_string_lossy())?.collect::<Result<_, _>>()?
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut inputs = Vec::new();
+    for root in roots {
+        if root.is_dir() {
+            if !recursive {
+                continue;
+            }
+            for entry in WalkDir::new(&root).sort_by_file_name() {
+                let entry = entry?;
+                if entry.file_type().is_file() && has_png_extension(entry.path()) {
+                    inputs.push(entry.into_path());
+                }
+            }
+        } else {
+            inputs.push(root);
+        }
+    }
+    if inputs.is_empty() {
+        return Err(Error::msg(format!("No PNG files found in {}", path.display())));
+    }
+    inputs.sort();
+    inputs.dedup();
+    Ok(inputs)
+}
+
+// 在线程池中对每个文件调用 process，输出先写入缓冲区，完成后整体写到标准输出，避免不同文件的输出交错
+// headers 为 true 时在每个文件的输出前加上 "==> 路径 <==" 标题
+// 每个文件完成时在标准错误输出中报告成功或失败，最后汇总；有文件失败时返回错误
+pub fn run<F>(inputs: &[PathBuf], jobs: Option<usize>, headers: bool, process: F) -> Result<()>
+where
+    F: Fn(&Path, &mut dyn Write) -> Result<()> + Sync,
+{
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs.unwrap_or(0)).build()?;
+    let (sender, receiver) = mpsc::channel();
+    let process = &process;
+    let mut failed = 0;
+    thread::scope(|scope| -> Result<()> {
+        scope.spawn(move || {
+            pool.install(|| {
+                inputs.par_iter().for_each_with(sender, |sender, path| {
+                    let mut output = Vec::new();
+                    let result = process(path, &mut output);
+                    // 接收端只在写标准输出失败时提前退出，此时丢弃剩余结果
+                    let _ = sender.send((path, output, result));
+                });
+            });
+        });
+        let mut stdout = io::stdout().lock();
+        for (path, output, result) in receiver {
+            if !output.is_empty() {
+                if headers {
+                    writeln!(stdout, "==> {} <==", path.display())?;
+                }
+                stdout.write_all(&output)?;
+            }
+            match result {
+                Ok(()) => eprintln!("ok: {}", path.display()),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("failed: {}: {:#}", path.display(), e);
+                }
+            }
+        }
+        stdout.flush()?;
+        Ok(())
+    })?;
+    eprintln!("{} file(s) processed: {} succeeded, {} failed", inputs.len(), inputs.len() - failed, failed);
+    if failed > 0 {
+        return Err(Error::msg(format!("{} of {} file(s) failed", failed, inputs.len())));
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_is_pattern() {
+        assert!(is_pattern(Path::new("images/*.png")));
+        assert!(is_pattern(Path::new("images/frame?.png")));
+        assert!(!is_pattern(Path::new("images/a.png")));
+    }
+
+    #[test]
+    fn test_collect_inputs() {
+        let dir = std::env::temp_dir().join(format!("pngme-batch-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["b.png", "a.PNG", "notes.txt", "nested/c.png"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        let names = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|p| p.strip_prefix(&dir).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect()
+        };
+        assert_eq!(names(collect_inputs(&dir, true).unwrap()), ["a.PNG", "b.png", "nested/c.png"]);
+        assert_eq!(names(collect_inputs(&dir.join("*.png"), false).unwrap()), ["b.png"]);
+        assert_eq!(names(collect_inputs(&dir.join("*"), true).unwrap()), ["a.PNG", "b.png", "nested/c.png", "notes.txt"]);
+        assert!(collect_inputs(&dir.join("*.gif"), false).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,29 @@
+#[cfg(feature = "attachment-codec")]
+use pngme_lib::Attachment;
+use pngme_lib::ChunkRegistry;
+
+// print 使用的数据块注册表：标准辅助数据块，以及由 feature 启用的私有数据块编解码器
+// 新增私有数据块时实现 ChunkCodec 与 Display，并在 Cargo.toml 中加上对应的 feature
+pub fn registry() -> ChunkRegistry {
+    #[cfg_attr(not(feature = "attachment-codec"), allow(unused_mut))]
+    let mut registry = ChunkRegistry::standard();
+    #[cfg(feature = "attachment-codec")]
+    registry.register::<Attachment>();
+    registry
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pngme_lib::ChunkType;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_registry() {
+        let registry = registry();
+        assert!(registry.is_registered(&ChunkType::from_str("pHYs").unwrap()));
+        let attachment = ChunkType::from_str("atCh").unwrap();
+        assert_eq!(registry.is_registered(&attachment), cfg!(feature = "attachment-codec"));
+    }
+}
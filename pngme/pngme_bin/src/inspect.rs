@@ -0,0 +1,160 @@
+use std::io::{self, Write};
+use pngme_lib::{Chunk, ChunkType};
+
+// 每行显示的字节数
+const BYTES_PER_LINE: usize = 16;
+
+// 突出显示有问题的字段：终端中用红色，否则加上 "!!" 前缀
+fn highlight(text: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[1;31m{}\x1b[0m", text)
+    } else {
+        format!("!! {}", text)
+    }
+}
+
+// 以 xxd 风格输出一个字段的十六进制与 ASCII 内容，注释只写在第一行
+fn write_field<W: Write>(out: &mut W, offset: u64, bytes: &[u8], label: &str) -> io::Result<()> {
+    if bytes.is_empty() {
+        return writeln!(out, "  {:08x}  {:<49}  {:<16}  {}", offset, "", "", label);
+    }
+    for (line_index, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        let mut hex = String::new();
+        for (i, byte) in line.iter().enumerate() {
+            if i == BYTES_PER_LINE / 2 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x} ", byte));
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        let label = if line_index == 0 { label } else { "" };
+        let line_offset = offset + (line_index * BYTES_PER_LINE) as u64;
+        let text = format!("  {:08x}  {:<49}  {:<16}  {}", line_offset, hex, ascii, label);
+        writeln!(out, "{}", text.trim_end())?;
+    }
+    Ok(())
+}
+
+// 数据块类型各属性位的说明
+fn describe_type(chunk_type: &ChunkType) -> String {
+    format!(
+        "{}, {}, {}",
+        if chunk_type.is_critical() { "critical" } else { "ancillary" },
+        if chunk_type.is_public() { "public" } else { "private" },
+        if chunk_type.is_safe_to_copy() { "safe to copy" } else { "unsafe to copy" },
+    )
+}
+
+// 输出文件开头的 PNG 签名
+pub fn write_signature<W: Write>(out: &mut W, bytes: &[u8]) -> io::Result<()> {
+    writeln!(out, "signature at offset 0")?;
+    write_field(out, 0, &bytes[..bytes.len().min(8)], "PNG signature")?;
+    writeln!(out)
+}
+
+// 输出一个数据块的注释十六进制转储；file 为整个文件内容，offset 为数据块的起始位置
+// max_bytes 限制数据字段显示的字节数
+pub fn write_chunk<W: Write>(
+    out: &mut W,
+    file: &[u8],
+    index: usize,
+    offset: u64,
+    chunk: &Chunk,
+    max_bytes: Option<usize>,
+    color: bool,
+) -> io::Result<()> {
+    let start = offset as usize;
+    let data_start = start + 8;
+    let data_end = data_start + chunk.length() as usize;
+    let crc_end = (data_end + 4).min(file.len());
+    writeln!(
+        out,
+        "chunk {}: {} at offset {} ({} bytes of data)",
+        index,
+        chunk.chunk_type(),
+        offset,
+        chunk.length()
+    )?;
+
+    let length_bytes = &file[start..start + 4];
+    let declared = u32::from_be_bytes([length_bytes[0], length_bytes[1], length_bytes[2], length_bytes[3]]);
+    let length_label = if declared == chunk.length() {
+        format!("length = {}", declared)
+    } else {
+        highlight(&format!("length = {} (file ends after {} bytes)", declared, chunk.length()), color)
+    };
+    write_field(out, offset, length_bytes, &length_label)?;
+
+    let type_label = format!("type = {} ({})", chunk.chunk_type(), describe_type(chunk.chunk_type()));
+    write_field(out, offset + 4, &file[start + 4..data_start], &type_label)?;
+
+    let shown = max_bytes.map_or(chunk.length() as usize, |max| max.min(chunk.length() as usize));
+    write_field(out, offset + 8, &file[data_start..data_start + shown], "data")?;
+    if shown < chunk.length() as usize {
+        writeln!(out, "  {:>8}  ... {} more bytes", "", chunk.length() as usize - shown)?;
+    }
+
+    let crc_bytes = &file[data_end..crc_end];
+    let crc_label = if crc_bytes.len() < 4 {
+        highlight("crc missing (file is truncated)", color)
+    } else if chunk.has_valid_crc() {
+        format!("crc = {:#010x} OK", chunk.crc())
+    } else {
+        let computed = Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()).crc();
+        highlight(&format!("crc = {:#010x} MISMATCH (computed {:#010x})", chunk.crc(), computed), color)
+    };
+    write_field(out, data_end as u64, crc_bytes, &crc_label)?;
+    writeln!(out)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pngme_lib::{ParseOptions, Png};
+    use std::str::FromStr;
+
+    fn dump(file: &[u8], chunk: &Chunk, max_bytes: Option<usize>) -> String {
+        let mut out = Vec::new();
+        write_chunk(&mut out, file, 0, 0, chunk, max_bytes, false).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_dump_valid_chunk() {
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"hello".to_vec());
+        let text = dump(&chunk.as_bytes(), &chunk, None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "chunk 0: RuSt at offset 0 (5 bytes of data)");
+        assert!(lines[1].starts_with("  00000000  00 00 00 05 "));
+        assert!(lines[1].ends_with("length = 5"));
+        assert!(lines[2].contains("RuSt") && lines[2].ends_with("type = RuSt (critical, private, safe to copy)"));
+        assert!(lines[3].starts_with("  00000008  68 65 6c 6c 6f "));
+        assert!(lines[3].contains("hello"));
+        assert!(lines[4].starts_with("  0000000d") && lines[4].ends_with("OK"));
+    }
+
+    #[test]
+    fn test_dump_highlights_crc_mismatch() {
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"hello".to_vec());
+        let mut file = Png::from_chunks(vec![chunk]).as_bytes();
+        let last = file.len() - 1;
+        file[last] ^= 1;
+        let (png, _) = Png::parse_with(file.as_slice(), ParseOptions::lenient()).unwrap();
+        let mut out = Vec::new();
+        write_chunk(&mut out, &file, 0, 8, &png.chunks()[0], None, false).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("!! crc = "));
+        assert!(text.contains("MISMATCH"));
+    }
+
+    #[test]
+    fn test_dump_limits_data() {
+        let chunk = Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![0; 40]);
+        let text = dump(&chunk.as_bytes(), &chunk, Some(16));
+        assert!(text.contains("... 24 more bytes"));
+    }
+}
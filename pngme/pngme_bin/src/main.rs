@@ -1,10 +1,22 @@
-use clap::Parser;
-use std::path::PathBuf;
-use anyhow::Result;
-use pngme_lib::{Png, Chunk, ChunkType};
-use std::fs::File;
-use std::io::{Read, Write};
+mod batch;
+mod codecs;
+mod inspect;
+mod output;
+mod swatch;
+
+use clap::{Parser, ValueEnum};
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Error};
+use memmap2::Mmap;
+use pngme_lib::{decrypt, encrypt, split_payload, Attachment, EncodeOptions, Png, PngError, PngReader, PngRef, Chunk, ChunkRef, ChunkRegistry, ChunkType, Ihdr, ParseOptions, Severity};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::env;
+use std::io::{self, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::ops::Deref;
 use std::str::FromStr;
+use output::{ChunkInfo, DecodedMessage, Format};
+use batch::BatchArgs;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -19,6 +31,27 @@ enum Commands {
     Decode(DecodeArgs),
     Remove(RemoveArgs),
     Print(PrintArgs),
+    Check(CheckArgs),
+    Attach(AttachArgs),
+    Extract(ExtractArgs),
+    Repair(RepairArgs),
+    Inspect(InspectArgs),
+    Diff(DiffArgs),
+    Strip(StripArgs),
+    Frames(FramesArgs),
+    Text(TextArgs),
+    Palette(PaletteArgs),
+}
+
+// 新数据块在文件中的插入位置
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Position {
+    // 紧挨在 IEND 之前
+    BeforeIend,
+    // 紧挨在 IHDR 之后
+    AfterIhdr,
+    // 追加到文件末尾（IEND 之后）
+    End,
 }
 
 #[derive(Parser, Debug)]
@@ -28,12 +61,43 @@ struct EncodeArgs {
     message: String,
     #[clap(short = 'o', long = "output")]
     output: Option<PathBuf>,
+    #[clap(long = "position", value_enum, default_value = "before-iend")]
+    position: Position,
+    // 指定插入的数据块下标，优先于 --position
+    #[clap(long = "index")]
+    index: Option<usize>,
+    // 用口令加密消息
+    #[clap(long = "encrypt")]
+    encrypt: bool,
+    // 加密口令；未指定时读取环境变量 PNGME_PASSPHRASE 或从标准输入读取
+    #[clap(long = "passphrase", requires = "encrypt")]
+    passphrase: Option<String>,
+    // 把消息拆分为多个同类型数据块，每段不超过指定字节数
+    #[clap(long = "segment-size")]
+    segment_size: Option<usize>,
+    #[clap(flatten)]
+    batch: BatchArgs,
 }
 
 #[derive(Parser, Debug)]
 struct DecodeArgs {
     file_path: PathBuf,
     chunk_type: String,
+    // 用口令解密消息
+    #[clap(long = "decrypt")]
+    decrypt: bool,
+    // 解密口令；未指定时读取环境变量 PNGME_PASSPHRASE 或从标准输入读取
+    #[clap(long = "passphrase", requires = "decrypt")]
+    passphrase: Option<String>,
+    // 重组由 --segment-size 拆分的多个数据块
+    #[clap(long = "segmented")]
+    segmented: bool,
+    // 以 json、ndjson 或表格格式输出
+    #[clap(long = "format", value_enum)]
+    format: Option<Format>,
+    // 在 json/ndjson 输出中包含 base64 编码的数据
+    #[clap(long = "data", requires = "format")]
+    data: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -45,6 +109,122 @@ struct RemoveArgs {
 #[derive(Parser, Debug)]
 struct PrintArgs {
     file_path: PathBuf,
+    // 宽松解析：跳过损坏的数据块并在标准错误输出中报告
+    #[clap(long = "lenient")]
+    lenient: bool,
+    // 以 json、ndjson 或表格格式输出
+    #[clap(long = "format", value_enum)]
+    format: Option<Format>,
+    // 在 json/ndjson 输出中包含 base64 编码的数据
+    #[clap(long = "data", requires = "format")]
+    data: bool,
+    #[clap(flatten)]
+    batch: BatchArgs,
+}
+
+#[derive(Parser, Debug)]
+struct CheckArgs {
+    file_path: PathBuf,
+    #[clap(flatten)]
+    batch: BatchArgs,
+}
+
+#[derive(Parser, Debug)]
+struct AttachArgs {
+    file_path: PathBuf,
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+    #[clap(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct ExtractArgs {
+    file_path: PathBuf,
+    // 提取到的目录，默认当前目录
+    #[clap(short = 'd', long = "dir", default_value = ".")]
+    dir: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct RepairArgs {
+    file_path: PathBuf,
+    #[clap(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct InspectArgs {
+    file_path: PathBuf,
+    // 只显示指定类型的数据块
+    #[clap(long = "chunk", conflicts_with = "index")]
+    chunk: Option<String>,
+    // 只显示指定下标的数据块
+    #[clap(long = "index")]
+    index: Option<usize>,
+    // 每个数据块最多显示的数据字节数
+    #[clap(long = "max-bytes")]
+    max_bytes: Option<usize>,
+}
+
+#[derive(Parser, Debug)]
+struct DiffArgs {
+    old_path: PathBuf,
+    new_path: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct StripArgs {
+    file_path: PathBuf,
+    #[clap(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+    // 始终保留的辅助数据块类型，以逗号分隔
+    #[clap(long = "keep", value_delimiter = ',')]
+    keep: Vec<String>,
+    // 只删除私有的辅助数据块
+    #[clap(long = "drop-private")]
+    drop_private: bool,
+    // 只删除不可安全复制的辅助数据块
+    #[clap(long = "drop-unsafe-to-copy")]
+    drop_unsafe_to_copy: bool,
+    #[clap(flatten)]
+    batch: BatchArgs,
+}
+
+#[derive(Parser, Debug)]
+struct FramesArgs {
+    file_path: PathBuf,
+    // 把合成后的每一帧保存为 PNG 文件的目录；未指定时只列出各帧
+    #[clap(short = 'x', long = "extract")]
+    extract: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct TextArgs {
+    #[clap(subcommand)]
+    command: TextCommand,
+}
+
+// 读写 tEXt、zTXt、iTXt 文本元数据
+#[derive(Parser, Debug)]
+enum TextCommand {
+    // 列出全部文本元数据
+    List { file_path: PathBuf },
+    // 输出指定关键字的文本
+    Get { file_path: PathBuf, keyword: String },
+    // 设置关键字的文本，替换已有的同关键字条目
+    Set {
+        file_path: PathBuf,
+        keyword: String,
+        value: String,
+        #[clap(short = 'o', long = "output")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Parser, Debug)]
+struct PaletteArgs {
+    file_path: PathBuf,
 }
 
 fn main() -> Result<()> {
@@ -54,49 +234,196 @@ fn main() -> Result<()> {
         Commands::Decode(args) => decode(args),
         Commands::Remove(args) => remove(args),
         Commands::Print(args) => print_chunks(args),
+        Commands::Check(args) => check(args),
+        Commands::Attach(args) => attach(args),
+        Commands::Extract(args) => extract(args),
+        Commands::Repair(args) => repair(args),
+        Commands::Inspect(args) => inspect_chunks(args),
+        Commands::Diff(args) => diff(args),
+        Commands::Strip(args) => strip(args),
+        Commands::Frames(args) => frames(args),
+        Commands::Text(args) => text(args),
+        Commands::Palette(args) => palette(args),
+    }
+}
+
+// 以流式方式读取并解析 PNG 文件
+fn read_png(path: &Path) -> Result<Png> {
+    let file = File::open(path)?;
+    Ok(Png::from_reader(BufReader::new(file))?)
+}
+
+// 整个文件的内容：普通文件只读映射，管道、设备等或映射失败时读入内存
+enum FileBytes {
+    Mapped(Mmap),
+    Read(Vec<u8>),
+}
+
+impl Deref for FileBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileBytes::Mapped(mmap) => mmap,
+            FileBytes::Read(data) => data,
+        }
+    }
+}
+
+// 读取整个文件，普通文件不复制数据
+fn map_file(mut file: File) -> Result<FileBytes> {
+    if file.metadata()?.is_file() {
+        // 安全性：映射的内存与文件内容共享。映射期间文件若被其他进程截断，访问超出新长度的页面会触发 SIGBUS；
+        // 若被修改，已借出的 &[u8] 内容会随之改变，两者都是未定义行为。
+        // 这里只在本进程内读取，并要求调用者保证处理期间没有其他进程修改或截断该文件
+        if let Ok(mmap) = unsafe { Mmap::map(&file) } {
+            return Ok(FileBytes::Mapped(mmap));
+        }
+    }
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(FileBytes::Read(data))
+}
+
+// 以流式方式把 PNG 写入文件
+fn write_png(path: &Path, png: &Png) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    Ok(png.write_to(&mut writer)?)
+}
+
+// 依次从命令行参数、环境变量 PNGME_PASSPHRASE、标准输入获取口令
+fn read_passphrase(passphrase: Option<String>) -> Result<String> {
+    let passphrase = match passphrase.or_else(|| env::var("PNGME_PASSPHRASE").ok()) {
+        Some(passphrase) => passphrase,
+        None => {
+            eprint!("Passphrase: ");
+            io::stderr().flush()?;
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    // 空口令无论来自参数、环境变量还是标准输入都拒绝
+    if passphrase.is_empty() {
+        return Err(Error::msg("Passphrase must not be empty"));
+    }
+    Ok(passphrase)
+}
+
+// 按位置参数依次插入数据块；找不到 IEND 或 IHDR 时退回到追加
+fn insert_chunks(png: &mut Png, chunks: Vec<Chunk>, position: Position, index: Option<usize>) -> Result<()> {
+    let len = png.chunks().len();
+    let find = |name: &str| png.chunks().iter().position(|c| c.chunk_type().to_string() == name);
+    let start = match (index, position) {
+        (Some(index), _) => index,
+        (None, Position::BeforeIend) => find("IEND").unwrap_or(len),
+        (None, Position::AfterIhdr) => find("IHDR").map_or(len, |i| i + 1),
+        (None, Position::End) => len,
+    };
+    for (offset, chunk) in chunks.into_iter().enumerate() {
+        png.insert_chunk(start + offset, chunk)?;
+    }
+    Ok(())
+}
+
+// 单个文件时直接处理并输出到标准输出；指定 --recursive 或 glob 模式时并行处理所有匹配的文件
+fn for_each_input<F>(path: &Path, batch: &BatchArgs, headers: bool, process: F) -> Result<()>
+where
+    F: Fn(&Path, &mut dyn Write) -> Result<()> + Sync,
+{
+    if !batch.is_batch(path) {
+        return process(path, &mut io::stdout().lock());
     }
+    let inputs = batch::collect_inputs(path, batch.recursive)?;
+    batch::run(&inputs, batch.jobs, headers, process)
+}
+
+// 批量处理时各文件原地修改，不能共用一个输出路径
+fn check_output(output: &Option<PathBuf>, path: &Path, batch: &BatchArgs) -> Result<()> {
+    if output.is_some() && batch.is_batch(path) {
+        return Err(Error::msg("--output cannot be used with --recursive or a glob pattern"));
+    }
+    Ok(())
 }
 
 fn encode(args: EncodeArgs) -> Result<()> {
-    let mut file = File::open(&args.file_path)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let mut png = Png::try_from(buffer.as_slice())?;
+    check_output(&args.output, &args.file_path, &args.batch)?;
     let chunk_type = ChunkType::from_str(&args.chunk_type)?;
-    let chunk = Chunk::new(chunk_type, args.message.into_bytes());
-    png.append_chunk(chunk);
-    let output_path = args.output.unwrap_or(args.file_path);
-    let mut output_file = File::create(output_path)?;
-    output_file.write_all(&png.as_bytes())?;
-    println!("Message encoded successfully.");
-    Ok(())
+    // 批量处理时只读取一次口令，每个文件仍使用各自的随机盐和随机数
+    let passphrase = match args.encrypt {
+        true => Some(read_passphrase(args.passphrase.clone())?),
+        false => None,
+    };
+    for_each_input(&args.file_path, &args.batch, true, |path, out| {
+        let mut png = read_png(path)?;
+        let mut data = args.message.as_bytes().to_vec();
+        if let Some(passphrase) = &passphrase {
+            data = encrypt(&data, passphrase)?;
+        }
+        let chunks = match args.segment_size {
+            Some(segment_size) => split_payload(chunk_type, &data, segment_size)?,
+            None => vec![Chunk::new(chunk_type, data)],
+        };
+        insert_chunks(&mut png, chunks, args.position, args.index)?;
+        let output_path = args.output.as_deref().unwrap_or(path);
+        write_png(output_path, &png)?;
+        writeln!(out, "Message encoded successfully.")?;
+        Ok(())
+    })
 }
 
 fn decode(args: DecodeArgs) -> Result<()> {
-    let mut file = File::open(args.file_path)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let png = Png::try_from(buffer.as_slice())?;
-    if let Some(chunk) = png.chunk_by_type(&args.chunk_type) {
-        if let Ok(message) = chunk.data_as_string() {
-            println!("Decoded message: {}", message);
-        } else {
-            println!("Failed to decode message as valid UTF-8.");
+    let png = read_png(&args.file_path)?;
+    // 消息所在数据块的下标
+    let indices: Vec<usize> = match ChunkType::from_str(&args.chunk_type) {
+        Ok(chunk_type) => png
+            .chunks()
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| *c.chunk_type() == chunk_type)
+            .map(|(index, _)| index)
+            .take(if args.segmented { usize::MAX } else { 1 })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    if indices.is_empty() {
+        if args.format.is_some() {
+            return Err(Error::msg(format!("Chunk of type {} not found", args.chunk_type)));
         }
-    } else {
         println!("Chunk of type {} not found.", args.chunk_type);
+        return Ok(());
+    }
+    let data = if args.segmented {
+        png.read_payload(&args.chunk_type)?
+    } else {
+        png.chunks()[indices[0]].data().to_vec()
+    };
+    let data = if args.decrypt {
+        let passphrase = read_passphrase(args.passphrase)?;
+        decrypt(&data, &passphrase)?
+    } else {
+        data
+    };
+    if let Some(format) = args.format {
+        let offsets = png.chunk_offsets();
+        let chunks = indices
+            .iter()
+            .map(|&index| ChunkInfo::new(index, offsets[index], &ChunkRef::from(&png.chunks()[index]), args.data))
+            .collect();
+        let decoded = DecodedMessage::new(&args.chunk_type, chunks, &data, args.data);
+        return output::write_decoded(&mut io::stdout().lock(), format, &decoded);
+    }
+    match String::from_utf8(data) {
+        Ok(message) => println!("Decoded message: {}", message),
+        Err(_) => println!("Failed to decode message as valid UTF-8."),
     }
     Ok(())
 }
 
 fn remove(args: RemoveArgs) -> Result<()> {
-    let mut file = File::open(&args.file_path)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let mut png = Png::try_from(buffer.as_slice())?;
+    let mut png = read_png(&args.file_path)?;
     if let Ok(_removed_chunk) = png.remove_first_chunk(&args.chunk_type) {
-        let mut output_file = File::create(args.file_path)?;
-        output_file.write_all(&png.as_bytes())?;
+        write_png(&args.file_path, &png)?;
         println!("Chunk of type {} removed successfully.", args.chunk_type);
     } else {
         println!("Chunk of type {} not found.", args.chunk_type);
@@ -105,12 +432,291 @@ fn remove(args: RemoveArgs) -> Result<()> {
 }
 
 fn print_chunks(args: PrintArgs) -> Result<()> {
-    let mut file = File::open(args.file_path)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let png = Png::try_from(buffer.as_slice())?;
-    for chunk in png.chunks() {
-        println!("{}", chunk);
+    // json/ndjson 输出不加文件标题，批量处理时改为在每个数据块中记录文件路径
+    let batch = args.batch.is_batch(&args.file_path);
+    // 每个文件各自输出一个 JSON 数组，拼接后不是合法的 JSON，批量处理时只支持逐行输出的 ndjson
+    if batch && matches!(args.format, Some(Format::Json)) {
+        return Err(Error::msg("--format json cannot be used with --recursive or a glob pattern, use --format ndjson"));
+    }
+    let headers = !matches!(args.format, Some(Format::Json | Format::Ndjson));
+    let registry = codecs::registry();
+    for_each_input(&args.file_path, &args.batch, headers, |path, out| {
+        let file_name = batch.then_some(path);
+        let file = File::open(path)?;
+        if args.lenient {
+            let (png, report) = Png::parse_with(BufReader::new(file), ParseOptions::lenient())?;
+            for issue in &report.issues {
+                eprintln!("warning: {}", issue);
+            }
+            // 丢弃过损坏数据时，偏移量对应修复后的文件布局
+            let chunks: Vec<ChunkRef> = png.chunks().iter().map(ChunkRef::from).collect();
+            return print_chunk_list(out, &args, &registry, file_name, png.ihdr(), &chunks, &png.chunk_offsets());
+        }
+        // 列出数据块时不复制数据
+        let bytes = map_file(file)?;
+        let png = PngRef::try_from(&bytes[..])?;
+        print_chunk_list(out, &args, &registry, file_name, png.ihdr(), png.chunks(), png.offsets())
+    })
+}
+
+fn print_chunk_list(
+    out: &mut dyn Write,
+    args: &PrintArgs,
+    registry: &ChunkRegistry,
+    file_name: Option<&Path>,
+    ihdr: pngme_lib::Result<Ihdr>,
+    chunks: &[ChunkRef],
+    offsets: &[u64],
+) -> Result<()> {
+    if let Some(format) = args.format {
+        let chunks: Vec<ChunkInfo> = chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| ChunkInfo {
+                file: file_name.map(|path| path.display().to_string()),
+                ..ChunkInfo::new(index, offsets[index], chunk, args.data)
+            })
+            .collect();
+        return output::write_chunks(out, format, &chunks);
+    }
+    match ihdr {
+        Ok(ihdr) => writeln!(out, "Image: {}", ihdr)?,
+        Err(e) => writeln!(out, "Image: unknown ({})", e)?,
+    }
+    // 已注册的数据块按字段含义输出，未注册或内容无效时退回原始格式
+    for chunk in chunks {
+        match registry.describe(chunk) {
+            Some(Ok(description)) => writeln!(out, "{}", description)?,
+            _ => writeln!(out, "{}", chunk)?,
+        }
+    }
+    Ok(())
+}
+
+fn check(args: CheckArgs) -> Result<()> {
+    for_each_input(&args.file_path, &args.batch, true, |path, out| {
+        let png = read_png(path)?;
+        let diagnostics = png.validate();
+        for diagnostic in &diagnostics {
+            writeln!(out, "{}", diagnostic)?;
+        }
+        let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+        if errors > 0 {
+            return Err(Error::msg(format!("{} error(s) found", errors)));
+        }
+        writeln!(out, "No errors found.")?;
+        Ok(())
+    })
+}
+
+fn attach(args: AttachArgs) -> Result<()> {
+    let mut png = read_png(&args.file_path)?;
+    let mut chunks = Vec::new();
+    for path in &args.files {
+        let attachment = Attachment::from_file(path)?;
+        println!("Attached {} ({} bytes, {})", attachment.name, attachment.data.len(), attachment.mime_type);
+        chunks.push(attachment.to_chunk()?);
+    }
+    insert_chunks(&mut png, chunks, Position::BeforeIend, None)?;
+    let output_path = args.output.unwrap_or(args.file_path);
+    write_png(&output_path, &png)?;
+    Ok(())
+}
+
+fn extract(args: ExtractArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    let attachments = png.attachments()?;
+    if attachments.is_empty() {
+        println!("No attachments found.");
+        return Ok(());
+    }
+    fs::create_dir_all(&args.dir)?;
+    let mut used = HashSet::new();
+    for attachment in attachments {
+        let path = args.dir.join(unique_name(&attachment.name, &mut used));
+        fs::write(&path, &attachment.data)?;
+        println!("Extracted {} ({} bytes, {})", path.display(), attachment.data.len(), attachment.mime_type);
+    }
+    Ok(())
+}
+
+// 同名附件依次改名为 "name (1).ext"、"name (2).ext"……，避免后提取的覆盖先提取的
+fn unique_name(name: &str, used: &mut HashSet<String>) -> String {
+    let path = Path::new(name);
+    let stem = path.file_stem().map_or(name.into(), |stem| stem.to_string_lossy());
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let mut candidate = name.to_string();
+    let mut count = 0;
+    while !used.insert(candidate.clone()) {
+        count += 1;
+        candidate = format!("{} ({}){}", stem, count, extension);
+    }
+    candidate
+}
+
+fn repair(args: RepairArgs) -> Result<()> {
+    let file = File::open(&args.file_path)?;
+    let (png, repairs) = Png::repair(BufReader::new(file))?;
+    if repairs.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+    for repair in &repairs {
+        println!("{}", repair);
+    }
+    let output_path = args.output.unwrap_or(args.file_path);
+    write_png(&output_path, &png)?;
+    println!("{} change(s) written to {}", repairs.len(), output_path.display());
+    Ok(())
+}
+
+fn inspect_chunks(args: InspectArgs) -> Result<()> {
+    let bytes = map_file(File::open(&args.file_path)?)?;
+    let mut reader = PngReader::with_options(&bytes[..], ParseOptions::lenient())?;
+    let color = io::stdout().is_terminal();
+    let mut out = io::stdout().lock();
+    if args.chunk.is_none() && args.index.is_none() {
+        inspect::write_signature(&mut out, &bytes)?;
+    }
+    let mut count = 0;
+    let mut shown = 0;
+    while let Some(chunk) = reader.next() {
+        let chunk = chunk?;
+        let offset = reader.last_offset().unwrap_or_default();
+        let selected = match (&args.chunk, args.index) {
+            (Some(chunk_type), _) => chunk.chunk_type().to_string() == *chunk_type,
+            (None, Some(index)) => index == count,
+            (None, None) => true,
+        };
+        if selected {
+            inspect::write_chunk(&mut out, &bytes, count, offset, &chunk, args.max_bytes, color)?;
+            shown += 1;
+        }
+        count += 1;
+    }
+    for issue in reader.issues() {
+        writeln!(out, "warning: {}", issue)?;
+    }
+    match (args.chunk, args.index) {
+        (Some(chunk_type), _) if shown == 0 => Err(PngError::ChunkNotFound(ChunkType::from_str(&chunk_type)?).into()),
+        (_, Some(index)) if shown == 0 => Err(PngError::IndexOutOfRange { index, len: count }.into()),
+        _ => Ok(()),
+    }
+}
+
+fn diff(args: DiffArgs) -> Result<()> {
+    let old = read_png(&args.old_path)?;
+    let new = read_png(&args.new_path)?;
+    let changes = old.diff(&new);
+    if changes.is_empty() {
+        println!("No chunk differences.");
+        return Ok(());
+    }
+    for change in &changes {
+        println!("{}", change);
+    }
+    println!("{} change(s)", changes.len());
+    Ok(())
+}
+
+// 删除辅助数据块，关键数据块和 --keep 列出的类型始终保留
+// 指定 --drop-private 或 --drop-unsafe-to-copy 时只删除满足任一条件的辅助数据块
+fn strip(args: StripArgs) -> Result<()> {
+    check_output(&args.output, &args.file_path, &args.batch)?;
+    let keep = args
+        .keep
+        .iter()
+        .map(|t| ChunkType::from_str(t))
+        .collect::<pngme_lib::Result<Vec<ChunkType>>>()?;
+    let filtered = args.drop_private || args.drop_unsafe_to_copy;
+    for_each_input(&args.file_path, &args.batch, true, |path, out| {
+        let mut png = read_png(path)?;
+        let removed = png.retain(|chunk| {
+            let chunk_type = chunk.chunk_type();
+            if chunk_type.is_critical() || keep.contains(chunk_type) {
+                return true;
+            }
+            let selected = (args.drop_private && !chunk_type.is_public())
+                || (args.drop_unsafe_to_copy && !chunk_type.is_safe_to_copy());
+            filtered && !selected
+        });
+        if removed.is_empty() {
+            writeln!(out, "No chunks removed.")?;
+            return Ok(());
+        }
+        for chunk in &removed {
+            writeln!(out, "Removed {} ({} bytes)", chunk.chunk_type(), chunk.length())?;
+        }
+        let output_path = args.output.as_deref().unwrap_or(path);
+        write_png(output_path, &png)?;
+        writeln!(out, "{} chunk(s) removed", removed.len())?;
+        Ok(())
+    })
+}
+
+fn frames(args: FramesArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    println!("Animation: {}", png.animation_control()?);
+    let Some(dir) = args.extract else {
+        for (index, control) in png.frame_controls()?.iter().enumerate() {
+            println!("frame {}: {}", index, control);
+        }
+        return Ok(());
+    };
+    let frames = png.frames()?;
+    fs::create_dir_all(&dir)?;
+    for (index, frame) in frames.enumerate() {
+        let frame = frame?;
+        let path = dir.join(format!("frame_{:04}.png", index));
+        write_png(&path, &Png::encode_image(&frame.image, EncodeOptions::default())?)?;
+        println!("frame {}: {} -> {}", index, frame.control, path.display());
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn text(args: TextArgs) -> Result<()> {
+    match args.command {
+        TextCommand::List { file_path } => {
+            let entries = read_png(&file_path)?.text_entries();
+            if entries.is_empty() {
+                println!("No text entries found.");
+            }
+            // 格式错误的文本数据块只报告警告，不影响其余文本
+            for entry in entries {
+                match entry {
+                    Ok(entry) => println!("{}", entry),
+                    Err(e) => eprintln!("warning: {}", e),
+                }
+            }
+        }
+        TextCommand::Get { file_path, keyword } => {
+            let entries = read_png(&file_path)?.text_entries();
+            match entries.iter().flatten().find(|entry| entry.keyword() == keyword) {
+                Some(entry) => println!("{}", entry.text()),
+                None => return Err(Error::msg(format!("No text entry with keyword {:?}", keyword))),
+            }
+        }
+        TextCommand::Set { file_path, keyword, value, output } => {
+            let mut png = read_png(&file_path)?;
+            png.set_text(&keyword, &value)?;
+            let output_path = output.unwrap_or(file_path);
+            write_png(&output_path, &png)?;
+            println!("Text entry {:?} set successfully.", keyword);
+        }
+    }
+    Ok(())
+}
+
+fn palette(args: PaletteArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    let palette = png.palette()?;
+    let transparency = match png.transparency() {
+        Ok(transparency) => Some(transparency),
+        Err(PngError::ChunkNotFound(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+    // 只在终端中绘制色块，重定向到文件时只输出颜色值
+    let color = io::stdout().is_terminal();
+    swatch::write_palette(&mut io::stdout().lock(), &palette, transparency.as_ref(), color)?;
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use std::io::Write;
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::ValueEnum;
+use pngme_lib::ChunkRef;
+use serde::Serialize;
+
+// 供脚本解析的输出格式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    // 带缩进的单个 JSON 文档
+    Json,
+    // 每行一个 JSON 对象
+    Ndjson,
+    // 对齐的文本表格
+    Table,
+}
+
+// 单个数据块的描述，offset 为数据块在文件中的起始字节偏移
+#[derive(Serialize, Debug)]
+pub struct ChunkInfo {
+    // 批量处理时数据块所在的文件
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub index: usize,
+    pub offset: u64,
+    pub length: u32,
+    #[serde(rename = "type")]
+    pub chunk_type: String,
+    pub critical: bool,
+    pub public: bool,
+    pub safe_to_copy: bool,
+    pub crc: u32,
+    pub crc_valid: bool,
+    // base64 编码的数据，仅在指定 --data 时输出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl ChunkInfo {
+    pub fn new(index: usize, offset: u64, chunk: &ChunkRef, include_data: bool) -> ChunkInfo {
+        let chunk_type = chunk.chunk_type();
+        ChunkInfo {
+            file: None,
+            index,
+            offset,
+            length: chunk.length(),
+            chunk_type: chunk_type.to_string(),
+            critical: chunk_type.is_critical(),
+            public: chunk_type.is_public(),
+            safe_to_copy: chunk_type.is_safe_to_copy(),
+            crc: chunk.crc(),
+            crc_valid: chunk.has_valid_crc(),
+            data: include_data.then(|| STANDARD.encode(chunk.data())),
+        }
+    }
+
+    // 表格中的一行，标志位依次为 关键/辅助、公有/私有、可复制/不可复制
+    fn table_row(&self) -> String {
+        let flags = format!(
+            "{}{}{}",
+            if self.critical { 'C' } else { 'a' },
+            if self.public { 'P' } else { 'p' },
+            if self.safe_to_copy { 'S' } else { 's' },
+        );
+        let crc = if self.crc_valid { "" } else { " (bad CRC)" };
+        format!(
+            "{:>5}  {:>10}  {:>10}  {:<4}  {:<5}  {:#010x}{}",
+            self.index, self.offset, self.length, self.chunk_type, flags, self.crc, crc
+        )
+    }
+}
+
+const TABLE_HEADER: &str = "INDEX      OFFSET      LENGTH  TYPE  FLAGS  CRC";
+
+// decode 子命令的输出：读取的数据块以及解码出的消息
+#[derive(Serialize, Debug)]
+pub struct DecodedMessage {
+    #[serde(rename = "type")]
+    pub chunk_type: String,
+    pub chunks: Vec<ChunkInfo>,
+    // 消息不是有效的 UTF-8 时为 null
+    pub message: Option<String>,
+    // base64 编码的消息原始字节，仅在指定 --data 时输出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl DecodedMessage {
+    pub fn new(chunk_type: &str, chunks: Vec<ChunkInfo>, message: &[u8], include_data: bool) -> DecodedMessage {
+        DecodedMessage {
+            chunk_type: chunk_type.to_string(),
+            chunks,
+            message: String::from_utf8(message.to_vec()).ok(),
+            data: include_data.then(|| STANDARD.encode(message)),
+        }
+    }
+}
+
+// 按格式输出数据块列表
+pub fn write_chunks<W: Write + ?Sized>(out: &mut W, format: Format, chunks: &[ChunkInfo]) -> Result<()> {
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, chunks)?;
+            writeln!(out)?;
+        }
+        Format::Ndjson => {
+            for chunk in chunks {
+                serde_json::to_writer(&mut *out, chunk)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Table => {
+            writeln!(out, "{}", TABLE_HEADER)?;
+            for chunk in chunks {
+                writeln!(out, "{}", chunk.table_row())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// 按格式输出解码结果；ndjson 格式下整个结果占一行
+pub fn write_decoded<W: Write + ?Sized>(out: &mut W, format: Format, decoded: &DecodedMessage) -> Result<()> {
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, decoded)?;
+            writeln!(out)?;
+        }
+        Format::Ndjson => {
+            serde_json::to_writer(&mut *out, decoded)?;
+            writeln!(out)?;
+        }
+        Format::Table => {
+            write_chunks(out, format, &decoded.chunks)?;
+            match &decoded.message {
+                Some(message) => writeln!(out, "Decoded message: {}", message)?,
+                None => writeln!(out, "Failed to decode message as valid UTF-8.")?,
+            }
+        }
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pngme_lib::{Chunk, ChunkType};
+    use std::str::FromStr;
+
+    fn testing_info(include_data: bool) -> ChunkInfo {
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"hi".to_vec());
+        ChunkInfo::new(2, 33, &ChunkRef::from(&chunk), include_data)
+    }
+
+    #[test]
+    fn test_chunk_info_json() {
+        let json = serde_json::to_value(testing_info(true)).unwrap();
+        assert_eq!(json["index"], 2);
+        assert_eq!(json["offset"], 33);
+        assert_eq!(json["type"], "RuSt");
+        assert_eq!(json["critical"], true);
+        assert_eq!(json["public"], false);
+        assert_eq!(json["safe_to_copy"], true);
+        assert_eq!(json["crc_valid"], true);
+        assert_eq!(json["data"], "aGk=");
+        let json = serde_json::to_value(testing_info(false)).unwrap();
+        assert!(json.get("data").is_none());
+    }
+
+    #[test]
+    fn test_ndjson_one_line_per_chunk() {
+        let mut out = Vec::new();
+        write_chunks(&mut out, Format::Ndjson, &[testing_info(false), testing_info(false)]).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        for line in text.lines() {
+            assert!(serde_json::from_str::<serde_json::Value>(line).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_table_row() {
+        assert_eq!(testing_info(false).table_row(), "    2          33           2  RuSt  CpS    0xd2bc3f73");
+    }
+}
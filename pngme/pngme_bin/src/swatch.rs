@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+use pngme_lib::{Palette, Transparency};
+
+// 每个色块占用的字符宽度
+const SWATCH_WIDTH: usize = 6;
+
+// 用 ANSI 真彩色背景绘制一个色块
+fn swatch(r: u8, g: u8, b: u8) -> String {
+    format!("\x1b[48;2;{};{};{}m{}\x1b[0m", r, g, b, " ".repeat(SWATCH_WIDTH))
+}
+
+// 逐行输出调色板的下标、颜色和透明度；color 为 true 时在颜色前绘制色块
+pub fn write_palette<W: Write>(
+    out: &mut W,
+    palette: &Palette,
+    transparency: Option<&Transparency>,
+    color: bool,
+) -> io::Result<()> {
+    writeln!(out, "Palette: {}", palette)?;
+    for (index, [r, g, b, alpha]) in palette.to_rgba(transparency).into_iter().enumerate() {
+        let mut line = format!("{:>5}  ", index);
+        if color {
+            line.push_str(&swatch(r, g, b));
+            line.push_str("  ");
+        }
+        line.push_str(&format!("#{:02x}{:02x}{:02x}", r, g, b));
+        if alpha != 255 {
+            line.push_str(&format!("  alpha {}", alpha));
+        }
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(color: bool) -> String {
+        let palette = Palette::new(vec![[255, 0, 0], [0, 128, 255]]).unwrap();
+        let transparency = Transparency::Alpha(vec![255, 64]);
+        let mut out = Vec::new();
+        write_palette(&mut out, &palette, Some(&transparency), color).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_write_palette() {
+        assert_eq!(render(false), "Palette: 2 entries\n    0  #ff0000\n    1  #0080ff  alpha 64\n");
+        let lines: Vec<String> = render(true).lines().map(String::from).collect();
+        assert_eq!(lines[1], "    0  \x1b[48;2;255;0;0m      \x1b[0m  #ff0000");
+    }
+}
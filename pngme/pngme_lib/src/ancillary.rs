@@ -0,0 +1,441 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::chunk::Chunk;
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+use crate::ihdr::{ColorType, Ihdr};
+
+// gAMA 与 cHRM 中的数值均为实际值乘以 100000
+const SCALE: f64 = 100_000.0;
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn invalid(message: String) -> PngError {
+    PngError::InvalidAncillary(message)
+}
+
+// sRGB 数据块中的渲染意图
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderingIntent {
+    Perceptual,
+    RelativeColorimetric,
+    Saturation,
+    AbsoluteColorimetric,
+}
+
+impl RenderingIntent {
+    // 返回在 sRGB 中的编码值
+    pub fn value(&self) -> u8 {
+        match self {
+            RenderingIntent::Perceptual => 0,
+            RenderingIntent::RelativeColorimetric => 1,
+            RenderingIntent::Saturation => 2,
+            RenderingIntent::AbsoluteColorimetric => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for RenderingIntent {
+    type Error = PngError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(RenderingIntent::Perceptual),
+            1 => Ok(RenderingIntent::RelativeColorimetric),
+            2 => Ok(RenderingIntent::Saturation),
+            3 => Ok(RenderingIntent::AbsoluteColorimetric),
+            _ => Err(invalid(format!("Invalid sRGB rendering intent: {}", value))),
+        }
+    }
+}
+
+impl fmt::Display for RenderingIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RenderingIntent::Perceptual => "perceptual",
+            RenderingIntent::RelativeColorimetric => "relative colorimetric",
+            RenderingIntent::Saturation => "saturation",
+            RenderingIntent::AbsoluteColorimetric => "absolute colorimetric",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// pHYs 数据块中像素尺寸的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhysicalUnit {
+    // 只表示像素的宽高比
+    Unknown,
+    Meter,
+}
+
+// bKGD 数据块中的背景色，格式取决于颜色类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    // 索引色图像：调色板下标
+    PaletteIndex(u8),
+    // 灰度图像：灰度样本值
+    Gray(u16),
+    // 真彩色图像：各通道样本值
+    Rgb(u16, u16, u16),
+}
+
+// 已知的标准辅助数据块，各字段按规范含义解析
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownChunk {
+    // gAMA：图像伽马值乘以 100000
+    Gamma(u32),
+    // cHRM：白点与三原色的 CIE x、y 坐标，均乘以 100000
+    Chromaticities {
+        white_point: (u32, u32),
+        red: (u32, u32),
+        green: (u32, u32),
+        blue: (u32, u32),
+    },
+    // sRGB：图像使用 sRGB 颜色空间以及渲染意图
+    Srgb(RenderingIntent),
+    // pHYs：每单位的像素数
+    PhysicalDimensions { x: u32, y: u32, unit: PhysicalUnit },
+    // tIME：最后修改时间（UTC）
+    Time { year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8 },
+    // bKGD：建议的背景色
+    Background(Background),
+    // sBIT：各通道的有效位数
+    SignificantBits(Vec<u8>),
+    // hIST：调色板中每种颜色的近似使用频率
+    Histogram(Vec<u16>),
+}
+
+impl KnownChunk {
+    // 能解析为 KnownChunk 的数据块类型
+    pub const CHUNK_TYPES: [&'static str; 8] = ["gAMA", "cHRM", "sRGB", "pHYs", "tIME", "bKGD", "sBIT", "hIST"];
+
+    // 返回对应的数据块类型
+    pub fn chunk_type(&self) -> ChunkType {
+        let name = match self {
+            KnownChunk::Gamma(_) => "gAMA",
+            KnownChunk::Chromaticities { .. } => "cHRM",
+            KnownChunk::Srgb(_) => "sRGB",
+            KnownChunk::PhysicalDimensions { .. } => "pHYs",
+            KnownChunk::Time { .. } => "tIME",
+            KnownChunk::Background(_) => "bKGD",
+            KnownChunk::SignificantBits(_) => "sBIT",
+            KnownChunk::Histogram(_) => "hIST",
+        };
+        ChunkType::from_str(name).unwrap()
+    }
+
+    // 按颜色类型和位深度检查 bKGD 与 sBIT 的格式与取值范围
+    pub fn validate(&self, ihdr: &Ihdr) -> Result<()> {
+        let max_sample = ((1u32 << ihdr.bit_depth) - 1) as u16;
+        match (self, ihdr.color_type) {
+            (KnownChunk::Background(Background::PaletteIndex(_)), ColorType::Indexed) => Ok(()),
+            (KnownChunk::Background(Background::Gray(gray)), ColorType::Grayscale | ColorType::GrayscaleAlpha) => {
+                if *gray > max_sample {
+                    return Err(invalid(format!("bKGD gray level {} exceeds bit depth {}", gray, ihdr.bit_depth)));
+                }
+                Ok(())
+            }
+            (KnownChunk::Background(Background::Rgb(r, g, b)), ColorType::Rgb | ColorType::Rgba) => {
+                if [r, g, b].iter().any(|&&v| v > max_sample) {
+                    return Err(invalid(format!("bKGD color exceeds bit depth {}", ihdr.bit_depth)));
+                }
+                Ok(())
+            }
+            (KnownChunk::Background(_), color_type) => {
+                Err(invalid(format!("bKGD format does not match color type {}", color_type)))
+            }
+            (KnownChunk::SignificantBits(bits), color_type) => {
+                // 索引色图像的 sBIT 描述调色板的 RGB 三个通道，每个通道 8 位
+                let (channels, depth) = match color_type {
+                    ColorType::Indexed => (3, 8),
+                    other => (other.channels(), ihdr.bit_depth),
+                };
+                if bits.len() != channels {
+                    return Err(invalid(format!(
+                        "sBIT has {} value(s) but color type {} requires {}",
+                        bits.len(),
+                        color_type,
+                        channels
+                    )));
+                }
+                if bits.iter().any(|&b| b > depth) {
+                    return Err(invalid(format!("sBIT values must not exceed the sample depth {}", depth)));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // 编码为数据块
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::new();
+        match self {
+            KnownChunk::Gamma(gamma) => data.extend_from_slice(&gamma.to_be_bytes()),
+            KnownChunk::Chromaticities { white_point, red, green, blue } => {
+                for (x, y) in [white_point, red, green, blue] {
+                    data.extend_from_slice(&x.to_be_bytes());
+                    data.extend_from_slice(&y.to_be_bytes());
+                }
+            }
+            KnownChunk::Srgb(intent) => data.push(intent.value()),
+            KnownChunk::PhysicalDimensions { x, y, unit } => {
+                data.extend_from_slice(&x.to_be_bytes());
+                data.extend_from_slice(&y.to_be_bytes());
+                data.push(match unit {
+                    PhysicalUnit::Unknown => 0,
+                    PhysicalUnit::Meter => 1,
+                });
+            }
+            KnownChunk::Time { year, month, day, hour, minute, second } => {
+                data.extend_from_slice(&year.to_be_bytes());
+                data.extend_from_slice(&[*month, *day, *hour, *minute, *second]);
+            }
+            KnownChunk::Background(Background::PaletteIndex(index)) => data.push(*index),
+            KnownChunk::Background(Background::Gray(gray)) => data.extend_from_slice(&gray.to_be_bytes()),
+            KnownChunk::Background(Background::Rgb(r, g, b)) => {
+                for value in [r, g, b] {
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            KnownChunk::SignificantBits(bits) => data.extend_from_slice(bits),
+            KnownChunk::Histogram(frequencies) => {
+                for frequency in frequencies {
+                    data.extend_from_slice(&frequency.to_be_bytes());
+                }
+            }
+        }
+        Chunk::new(self.chunk_type(), data)
+    }
+}
+
+// 检查数据长度是否为 expected 之一
+fn expect_length(name: &str, data: &[u8], expected: &[usize]) -> Result<()> {
+    if !expected.contains(&data.len()) {
+        return Err(invalid(format!("Invalid {} length: {}", name, data.len())));
+    }
+    Ok(())
+}
+
+impl KnownChunk {
+    // 按数据块类型名称解析数据，供 TryFrom 和 ChunkRegistry 使用
+    pub(crate) fn parse(name: &str, data: &[u8]) -> Result<KnownChunk> {
+        match name {
+            "gAMA" => {
+                expect_length(name, data, &[4])?;
+                let gamma = read_u32(data, 0);
+                if gamma == 0 {
+                    return Err(invalid("gAMA must not be zero".to_string()));
+                }
+                Ok(KnownChunk::Gamma(gamma))
+            }
+            "cHRM" => {
+                expect_length(name, data, &[32])?;
+                let point = |i: usize| (read_u32(data, i * 8), read_u32(data, i * 8 + 4));
+                Ok(KnownChunk::Chromaticities {
+                    white_point: point(0),
+                    red: point(1),
+                    green: point(2),
+                    blue: point(3),
+                })
+            }
+            "sRGB" => {
+                expect_length(name, data, &[1])?;
+                Ok(KnownChunk::Srgb(RenderingIntent::try_from(data[0])?))
+            }
+            "pHYs" => {
+                expect_length(name, data, &[9])?;
+                let unit = match data[8] {
+                    0 => PhysicalUnit::Unknown,
+                    1 => PhysicalUnit::Meter,
+                    other => return Err(invalid(format!("Invalid pHYs unit: {}", other))),
+                };
+                Ok(KnownChunk::PhysicalDimensions {
+                    x: read_u32(data, 0),
+                    y: read_u32(data, 4),
+                    unit,
+                })
+            }
+            "tIME" => {
+                expect_length(name, data, &[7])?;
+                let (month, day, hour, minute, second) = (data[2], data[3], data[4], data[5], data[6]);
+                // 秒允许为 60 以表示闰秒
+                if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+                    return Err(invalid(format!("Invalid tIME value: {:?}", &data[2..])));
+                }
+                Ok(KnownChunk::Time {
+                    year: read_u16(data, 0),
+                    month,
+                    day,
+                    hour,
+                    minute,
+                    second,
+                })
+            }
+            "bKGD" => {
+                expect_length(name, data, &[1, 2, 6])?;
+                Ok(KnownChunk::Background(match data.len() {
+                    1 => Background::PaletteIndex(data[0]),
+                    2 => Background::Gray(read_u16(data, 0)),
+                    _ => Background::Rgb(read_u16(data, 0), read_u16(data, 2), read_u16(data, 4)),
+                }))
+            }
+            "sBIT" => {
+                expect_length(name, data, &[1, 2, 3, 4])?;
+                if data.contains(&0) {
+                    return Err(invalid("sBIT values must be greater than zero".to_string()));
+                }
+                Ok(KnownChunk::SignificantBits(data.to_vec()))
+            }
+            "hIST" => {
+                if data.is_empty() || !data.len().is_multiple_of(2) || data.len() > 512 {
+                    return Err(invalid(format!("Invalid hIST length: {}", data.len())));
+                }
+                Ok(KnownChunk::Histogram(data.chunks_exact(2).map(|pair| read_u16(pair, 0)).collect()))
+            }
+            _ => Err(invalid(format!("{} is not a known ancillary chunk", name))),
+        }
+    }
+}
+
+impl TryFrom<&ChunkRef<'_>> for KnownChunk {
+    type Error = PngError;
+
+    fn try_from(chunk: &ChunkRef<'_>) -> Result<Self> {
+        KnownChunk::parse(&chunk.chunk_type().to_string(), chunk.data())
+    }
+}
+
+impl TryFrom<&Chunk> for KnownChunk {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        KnownChunk::try_from(&ChunkRef::from(chunk))
+    }
+}
+
+// 每米像素数换算为每英寸像素数
+fn dpi(pixels_per_meter: u32) -> u32 {
+    (pixels_per_meter as f64 * 0.0254).round() as u32
+}
+
+impl fmt::Display for KnownChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.chunk_type())?;
+        match self {
+            KnownChunk::Gamma(gamma) => write!(f, "{:.5} (display gamma {:.2})", *gamma as f64 / SCALE, SCALE / *gamma as f64),
+            KnownChunk::Chromaticities { white_point, red, green, blue } => {
+                let names = ["white", "red", "green", "blue"];
+                for (i, (x, y)) in [white_point, red, green, blue].into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} ({:.4}, {:.4})", names[i], *x as f64 / SCALE, *y as f64 / SCALE)?;
+                }
+                Ok(())
+            }
+            KnownChunk::Srgb(intent) => write!(f, "{} rendering intent", intent),
+            KnownChunk::PhysicalDimensions { x, y, unit: PhysicalUnit::Meter } => {
+                if dpi(*x) == dpi(*y) {
+                    write!(f, "{}x{} px/m ({} DPI)", x, y, dpi(*x))
+                } else {
+                    write!(f, "{}x{} px/m ({}x{} DPI)", x, y, dpi(*x), dpi(*y))
+                }
+            }
+            KnownChunk::PhysicalDimensions { x, y, unit: PhysicalUnit::Unknown } => write!(f, "{}:{} pixel aspect ratio", x, y),
+            KnownChunk::Time { year, month, day, hour, minute, second } => write!(
+                f,
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+                year, month, day, hour, minute, second
+            ),
+            KnownChunk::Background(Background::PaletteIndex(index)) => write!(f, "palette index {}", index),
+            KnownChunk::Background(Background::Gray(gray)) => write!(f, "gray {}", gray),
+            KnownChunk::Background(Background::Rgb(r, g, b)) => write!(f, "rgb({}, {}, {})", r, g, b),
+            KnownChunk::SignificantBits(bits) => {
+                let bits: Vec<String> = bits.iter().map(u8::to_string).collect();
+                write!(f, "{} significant bits", bits.join(", "))
+            }
+            KnownChunk::Histogram(frequencies) => write!(f, "{} palette entries", frequencies.len()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::InterlaceMethod;
+
+    fn parse(chunk_type: &str, data: &[u8]) -> Result<KnownChunk> {
+        KnownChunk::try_from(&Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec()))
+    }
+
+    #[test]
+    fn test_display() {
+        let phys = parse("pHYs", &[0, 0, 0x0B, 0x13, 0, 0, 0x0B, 0x13, 1]).unwrap();
+        assert_eq!(phys.to_string(), "pHYs: 2835x2835 px/m (72 DPI)");
+        assert_eq!(parse("gAMA", &45455u32.to_be_bytes()).unwrap().to_string(), "gAMA: 0.45455 (display gamma 2.20)");
+        assert_eq!(parse("sRGB", &[0]).unwrap().to_string(), "sRGB: perceptual rendering intent");
+        assert_eq!(parse("tIME", &[0x07, 0xE8, 2, 29, 13, 5, 9]).unwrap().to_string(), "tIME: 2024-02-29 13:05:09 UTC");
+        assert_eq!(parse("bKGD", &[0, 255, 0, 255, 0, 255]).unwrap().to_string(), "bKGD: rgb(255, 255, 255)");
+        assert_eq!(parse("sBIT", &[5, 6, 5]).unwrap().to_string(), "sBIT: 5, 6, 5 significant bits");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let chunks = [
+            KnownChunk::Gamma(45455),
+            KnownChunk::Chromaticities {
+                white_point: (31270, 32900),
+                red: (64000, 33000),
+                green: (30000, 60000),
+                blue: (15000, 6000),
+            },
+            KnownChunk::Srgb(RenderingIntent::Saturation),
+            KnownChunk::PhysicalDimensions { x: 1, y: 2, unit: PhysicalUnit::Unknown },
+            KnownChunk::Time { year: 2000, month: 1, day: 1, hour: 0, minute: 0, second: 60 },
+            KnownChunk::Background(Background::Gray(7)),
+            KnownChunk::SignificantBits(vec![8, 8]),
+            KnownChunk::Histogram(vec![1, 2, 3]),
+        ];
+        for known in chunks {
+            assert_eq!(KnownChunk::try_from(&known.to_chunk()).unwrap(), known);
+        }
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        assert!(parse("gAMA", &[0, 0, 0, 0]).is_err());
+        assert!(parse("gAMA", &[0, 0, 1]).is_err());
+        assert!(parse("sRGB", &[4]).is_err());
+        assert!(parse("pHYs", &[0, 0, 0, 1, 0, 0, 0, 1, 2]).is_err());
+        assert!(parse("tIME", &[0x07, 0xE8, 13, 1, 0, 0, 0]).is_err());
+        assert!(parse("bKGD", &[0, 0, 0]).is_err());
+        assert!(parse("sBIT", &[0]).is_err());
+        assert!(parse("hIST", &[0]).is_err());
+        assert!(parse("IDAT", &[]).is_err());
+    }
+
+    #[test]
+    fn test_validate_against_ihdr() {
+        let gray = Ihdr::new(1, 1, 4, ColorType::Grayscale, InterlaceMethod::None).unwrap();
+        assert!(KnownChunk::Background(Background::Gray(15)).validate(&gray).is_ok());
+        assert!(KnownChunk::Background(Background::Gray(16)).validate(&gray).is_err());
+        assert!(KnownChunk::Background(Background::PaletteIndex(0)).validate(&gray).is_err());
+        assert!(KnownChunk::SignificantBits(vec![4]).validate(&gray).is_ok());
+        assert!(KnownChunk::SignificantBits(vec![5]).validate(&gray).is_err());
+        let indexed = Ihdr::new(1, 1, 2, ColorType::Indexed, InterlaceMethod::None).unwrap();
+        assert!(KnownChunk::SignificantBits(vec![8, 8, 8]).validate(&indexed).is_ok());
+        assert!(KnownChunk::SignificantBits(vec![8]).validate(&indexed).is_err());
+    }
+}
@@ -0,0 +1,700 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+use crate::ihdr::{ColorType, Ihdr};
+use crate::image::{self, Image};
+
+// 合成动画时画布最多包含的像素数，超出时拒绝分配画布
+const MAX_CANVAS_PIXELS: usize = 1 << 28;
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+// 检查数据块类型是否为期望的类型
+fn expect_type(chunk: &Chunk, expected: ChunkType) -> Result<()> {
+    if *chunk.chunk_type() != expected {
+        return Err(PngError::InvalidAnimation(format!("Expected {} chunk, found {}", expected, chunk.chunk_type())));
+    }
+    Ok(())
+}
+
+// acTL 数据块：动画的帧数和播放次数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationControl {
+    pub num_frames: u32,
+    // 0 表示无限循环
+    pub num_plays: u32,
+}
+
+impl AnimationControl {
+    // acTL 数据块的数据长度固定为 8 字节
+    pub const LENGTH: usize = 8;
+
+    // 返回 acTL 的数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("acTL").unwrap()
+    }
+
+    // 将动画控制信息编码为数据块
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(Self::LENGTH);
+        data.extend_from_slice(&self.num_frames.to_be_bytes());
+        data.extend_from_slice(&self.num_plays.to_be_bytes());
+        Chunk::new(Self::chunk_type(), data)
+    }
+}
+
+impl TryFrom<&[u8]> for AnimationControl {
+    type Error = PngError;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        if value.len() != Self::LENGTH {
+            return Err(PngError::InvalidAnimation(format!("Invalid acTL length: {}", value.len())));
+        }
+        let num_frames = read_u32(value, 0);
+        if num_frames == 0 {
+            return Err(PngError::InvalidAnimation("acTL must declare at least one frame".to_string()));
+        }
+        Ok(AnimationControl {
+            num_frames,
+            num_plays: read_u32(value, 4),
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for AnimationControl {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        expect_type(chunk, Self::chunk_type())?;
+        AnimationControl::try_from(chunk.data())
+    }
+}
+
+impl fmt::Display for AnimationControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.num_plays {
+            0 => write!(f, "{} frame(s), looping forever", self.num_frames),
+            plays => write!(f, "{} frame(s), played {} time(s)", self.num_frames, plays),
+        }
+    }
+}
+
+// 显示下一帧之前如何处置当前帧的区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisposeOp {
+    // 保留不变
+    None,
+    // 清除为完全透明的黑色
+    Background,
+    // 恢复为显示该帧之前的内容
+    Previous,
+}
+
+impl DisposeOp {
+    // 返回在 fcTL 中的编码值
+    pub fn value(&self) -> u8 {
+        match self {
+            DisposeOp::None => 0,
+            DisposeOp::Background => 1,
+            DisposeOp::Previous => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for DisposeOp {
+    type Error = PngError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(DisposeOp::None),
+            1 => Ok(DisposeOp::Background),
+            2 => Ok(DisposeOp::Previous),
+            _ => Err(PngError::InvalidAnimation(format!("Invalid dispose op: {}", value))),
+        }
+    }
+}
+
+impl fmt::Display for DisposeOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisposeOp::None => write!(f, "none"),
+            DisposeOp::Background => write!(f, "background"),
+            DisposeOp::Previous => write!(f, "previous"),
+        }
+    }
+}
+
+// 帧的像素如何写入画布
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendOp {
+    // 直接覆盖帧区域（包括 alpha）
+    Source,
+    // 按 alpha 叠加到现有内容之上
+    Over,
+}
+
+impl BlendOp {
+    // 返回在 fcTL 中的编码值
+    pub fn value(&self) -> u8 {
+        match self {
+            BlendOp::Source => 0,
+            BlendOp::Over => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for BlendOp {
+    type Error = PngError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(BlendOp::Source),
+            1 => Ok(BlendOp::Over),
+            _ => Err(PngError::InvalidAnimation(format!("Invalid blend op: {}", value))),
+        }
+    }
+}
+
+impl fmt::Display for BlendOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlendOp::Source => write!(f, "source"),
+            BlendOp::Over => write!(f, "over"),
+        }
+    }
+}
+
+// fcTL 数据块：一帧在画布中的区域、显示时长以及处置和混合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameControl {
+    pub sequence_number: u32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: u32,
+    pub y_offset: u32,
+    // 显示时长为 delay_num / delay_den 秒
+    pub delay_num: u16,
+    pub delay_den: u16,
+    pub dispose_op: DisposeOp,
+    pub blend_op: BlendOp,
+}
+
+impl FrameControl {
+    // fcTL 数据块的数据长度固定为 26 字节
+    pub const LENGTH: usize = 26;
+
+    // 返回 fcTL 的数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("fcTL").unwrap()
+    }
+
+    // 显示时长（秒），分母为 0 时按 100 计算
+    pub fn delay(&self) -> f64 {
+        let den = if self.delay_den == 0 { 100 } else { self.delay_den };
+        self.delay_num as f64 / den as f64
+    }
+
+    // 检查帧区域是否非空且完全位于 IHDR 描述的画布之内
+    pub fn validate(&self, ihdr: &Ihdr) -> Result<()> {
+        if self.width == 0 || self.height == 0 {
+            return Err(PngError::InvalidAnimation(format!(
+                "Frame {} has an empty region",
+                self.sequence_number
+            )));
+        }
+        let right = self.x_offset as u64 + self.width as u64;
+        let bottom = self.y_offset as u64 + self.height as u64;
+        if right > ihdr.width as u64 || bottom > ihdr.height as u64 {
+            return Err(PngError::InvalidAnimation(format!(
+                "Frame {} region {}x{} at ({}, {}) exceeds the {}x{} canvas",
+                self.sequence_number, self.width, self.height, self.x_offset, self.y_offset, ihdr.width, ihdr.height
+            )));
+        }
+        Ok(())
+    }
+
+    // 将帧控制信息编码为数据块
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(Self::LENGTH);
+        for value in [self.sequence_number, self.width, self.height, self.x_offset, self.y_offset] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&self.delay_num.to_be_bytes());
+        data.extend_from_slice(&self.delay_den.to_be_bytes());
+        data.push(self.dispose_op.value());
+        data.push(self.blend_op.value());
+        Chunk::new(Self::chunk_type(), data)
+    }
+}
+
+impl TryFrom<&[u8]> for FrameControl {
+    type Error = PngError;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        if value.len() != Self::LENGTH {
+            return Err(PngError::InvalidAnimation(format!("Invalid fcTL length: {}", value.len())));
+        }
+        Ok(FrameControl {
+            sequence_number: read_u32(value, 0),
+            width: read_u32(value, 4),
+            height: read_u32(value, 8),
+            x_offset: read_u32(value, 12),
+            y_offset: read_u32(value, 16),
+            delay_num: read_u16(value, 20),
+            delay_den: read_u16(value, 22),
+            dispose_op: DisposeOp::try_from(value[24])?,
+            blend_op: BlendOp::try_from(value[25])?,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for FrameControl {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        expect_type(chunk, Self::chunk_type())?;
+        FrameControl::try_from(chunk.data())
+    }
+}
+
+impl fmt::Display for FrameControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} at ({}, {}), delay {}/{} ({:.3} s), dispose: {}, blend: {}",
+            self.width,
+            self.height,
+            self.x_offset,
+            self.y_offset,
+            self.delay_num,
+            self.delay_den,
+            self.delay(),
+            self.dispose_op,
+            self.blend_op
+        )
+    }
+}
+
+// fdAT 数据块：除第一帧外各帧的压缩图像数据，格式与 IDAT 相同但带有序号
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameData {
+    pub sequence_number: u32,
+    pub data: Vec<u8>,
+}
+
+impl FrameData {
+    // 返回 fdAT 的数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("fdAT").unwrap()
+    }
+
+    // 将帧数据编码为数据块
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(4 + self.data.len());
+        data.extend_from_slice(&self.sequence_number.to_be_bytes());
+        data.extend_from_slice(&self.data);
+        Chunk::new(Self::chunk_type(), data)
+    }
+}
+
+impl TryFrom<&[u8]> for FrameData {
+    type Error = PngError;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        if value.len() < 4 {
+            return Err(PngError::InvalidAnimation(format!("Invalid fdAT length: {}", value.len())));
+        }
+        Ok(FrameData {
+            sequence_number: read_u32(value, 0),
+            data: value[4..].to_vec(),
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for FrameData {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        expect_type(chunk, Self::chunk_type())?;
+        FrameData::try_from(chunk.data())
+    }
+}
+
+// 动画中合成后的一帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub control: FrameControl,
+    // 合成后的整个画布，8 位 RGBA
+    pub image: Image,
+}
+
+// 一帧的控制信息以及按顺序存放其压缩数据的数据块切片
+#[derive(Debug, Clone)]
+pub(crate) struct RawFrame<'a> {
+    pub(crate) control: FrameControl,
+    data: Vec<&'a [u8]>,
+    // 第一帧也可以是默认图像，此时数据来自 IDAT
+    uses_idat: bool,
+}
+
+// fcTL 与 fdAT 共用一个从 0 开始连续递增的序号
+fn check_sequence(name: &str, sequence_number: u32, next: &mut u32) -> Result<()> {
+    if sequence_number != *next {
+        return Err(PngError::InvalidAnimation(format!(
+            "{} sequence number {} out of order, expected {}",
+            name, sequence_number, next
+        )));
+    }
+    *next += 1;
+    Ok(())
+}
+
+// 按顺序收集 acTL 与全部帧，校验序号、帧数以及帧区域
+pub(crate) fn parse_frames<'a>(ihdr: &Ihdr, chunks: &'a [Chunk]) -> Result<(AnimationControl, Vec<RawFrame<'a>>)> {
+    let mut animation = None;
+    let mut frames: Vec<RawFrame> = Vec::new();
+    let mut next_sequence = 0;
+    let mut seen_idat = false;
+    for chunk in chunks {
+        match chunk.chunk_type().to_string().as_str() {
+            "acTL" => {
+                if animation.is_some() {
+                    return Err(PngError::InvalidAnimation("Duplicate acTL chunk".to_string()));
+                }
+                if seen_idat {
+                    return Err(PngError::InvalidAnimation("acTL must appear before IDAT".to_string()));
+                }
+                animation = Some(AnimationControl::try_from(chunk)?);
+            }
+            "fcTL" => {
+                let control = FrameControl::try_from(chunk)?;
+                check_sequence("fcTL", control.sequence_number, &mut next_sequence)?;
+                control.validate(ihdr)?;
+                let uses_idat = frames.is_empty() && !seen_idat;
+                if uses_idat && (control.x_offset, control.y_offset, control.width, control.height) != (0, 0, ihdr.width, ihdr.height) {
+                    return Err(PngError::InvalidAnimation("The fcTL for the default image must cover the whole canvas".to_string()));
+                }
+                frames.push(RawFrame {
+                    control,
+                    data: Vec::new(),
+                    uses_idat,
+                });
+            }
+            "IDAT" => {
+                seen_idat = true;
+                if let [frame] = frames.as_mut_slice() {
+                    if frame.uses_idat {
+                        frame.data.push(chunk.data());
+                    }
+                }
+            }
+            "fdAT" => {
+                let data = chunk.data();
+                if data.len() < 4 {
+                    return Err(PngError::InvalidAnimation(format!("Invalid fdAT length: {}", data.len())));
+                }
+                check_sequence("fdAT", read_u32(data, 0), &mut next_sequence)?;
+                match frames.last_mut() {
+                    Some(frame) if !frame.uses_idat => frame.data.push(&data[4..]),
+                    _ => return Err(PngError::InvalidAnimation("fdAT chunk without a preceding fcTL".to_string())),
+                }
+            }
+            _ => {}
+        }
+    }
+    let animation = animation.ok_or_else(|| PngError::ChunkNotFound(AnimationControl::chunk_type()))?;
+    if animation.num_frames as usize != frames.len() {
+        return Err(PngError::InvalidAnimation(format!(
+            "acTL declares {} frame(s) but {} fcTL chunk(s) found",
+            animation.num_frames,
+            frames.len()
+        )));
+    }
+    if let Some(frame) = frames.iter().find(|f| f.data.is_empty()) {
+        return Err(PngError::InvalidAnimation(format!(
+            "Frame {} has no image data",
+            frame.control.sequence_number
+        )));
+    }
+    Ok((animation, frames))
+}
+
+// 按 alpha 把 src 像素叠加到 dst 像素之上
+fn blend_over(dst: &mut [u8], src: &[u8]) {
+    let src_alpha = src[3] as u32;
+    if src_alpha == 255 {
+        dst.copy_from_slice(src);
+        return;
+    }
+    if src_alpha == 0 {
+        return;
+    }
+    let dst_alpha = dst[3] as u32 * (255 - src_alpha) / 255;
+    let alpha = src_alpha + dst_alpha;
+    for i in 0..3 {
+        dst[i] = ((src[i] as u32 * src_alpha + dst[i] as u32 * dst_alpha) / alpha) as u8;
+    }
+    dst[3] = alpha as u8;
+}
+
+// 依次解码并合成动画各帧的迭代器
+// 每一帧按 blend_op 写入画布后产出整个画布，再在下一帧之前按 dispose_op 处置
+pub struct Frames<'a> {
+    ihdr: Ihdr,
+    animation: AnimationControl,
+    palette: Option<&'a [u8]>,
+    transparency: Option<&'a [u8]>,
+    frames: std::vec::IntoIter<RawFrame<'a>>,
+    // 8 位 RGBA 画布，初始为完全透明的黑色
+    canvas: Vec<u8>,
+    // 上一帧的控制信息，以及 dispose_op 为 Previous 时保存的画布
+    pending: Option<(FrameControl, Option<Vec<u8>>)>,
+}
+
+impl<'a> Frames<'a> {
+    pub(crate) fn new(
+        ihdr: Ihdr,
+        animation: AnimationControl,
+        frames: Vec<RawFrame<'a>>,
+        palette: Option<&'a [u8]>,
+        transparency: Option<&'a [u8]>,
+    ) -> Result<Frames<'a>> {
+        let pixels = (ihdr.width as usize)
+            .checked_mul(ihdr.height as usize)
+            .filter(|&pixels| pixels <= MAX_CANVAS_PIXELS)
+            .ok_or_else(|| {
+                PngError::InvalidAnimation(format!(
+                    "Canvas of {}x{} exceeds the limit of {} pixels",
+                    ihdr.width, ihdr.height, MAX_CANVAS_PIXELS
+                ))
+            })?;
+        Ok(Frames {
+            ihdr,
+            animation,
+            palette,
+            transparency,
+            frames: frames.into_iter(),
+            canvas: vec![0; pixels * 4],
+            pending: None,
+        })
+    }
+
+    // 返回 acTL 中的动画控制信息
+    pub fn animation_control(&self) -> AnimationControl {
+        self.animation
+    }
+
+    // 对画布中帧区域的每一行调用 f，参数为该行在画布中的像素切片与帧内行号
+    fn for_each_row<F: FnMut(&mut [u8], usize)>(canvas: &mut [u8], canvas_width: u32, control: &FrameControl, mut f: F) {
+        let stride = canvas_width as usize * 4;
+        for row in 0..control.height as usize {
+            let start = (control.y_offset as usize + row) * stride + control.x_offset as usize * 4;
+            f(&mut canvas[start..start + control.width as usize * 4], row);
+        }
+    }
+
+    // 按上一帧的 dispose_op 处置画布
+    fn dispose(&mut self) {
+        match self.pending.take() {
+            Some((control, _)) if control.dispose_op == DisposeOp::Background => {
+                Self::for_each_row(&mut self.canvas, self.ihdr.width, &control, |row, _| row.fill(0));
+            }
+            Some((_, Some(saved))) => self.canvas = saved,
+            _ => {}
+        }
+    }
+
+    fn compose(&mut self, raw: RawFrame<'a>) -> Result<Frame> {
+        self.dispose();
+        let control = raw.control;
+        let frame_ihdr = Ihdr {
+            width: control.width,
+            height: control.height,
+            ..self.ihdr
+        };
+        let image = image::decode(&frame_ihdr, &raw.data.concat())?;
+        let pixels = image.to_rgba8(self.palette, self.transparency)?;
+        let saved = (control.dispose_op == DisposeOp::Previous).then(|| self.canvas.clone());
+        let row_length = control.width as usize * 4;
+        Self::for_each_row(&mut self.canvas, self.ihdr.width, &control, |row, y| {
+            let source = &pixels[y * row_length..(y + 1) * row_length];
+            match control.blend_op {
+                BlendOp::Source => row.copy_from_slice(source),
+                BlendOp::Over => {
+                    for (dst, src) in row.chunks_exact_mut(4).zip(source.chunks_exact(4)) {
+                        blend_over(dst, src);
+                    }
+                }
+            }
+        });
+        self.pending = Some((control, saved));
+        Ok(Frame {
+            control,
+            image: Image {
+                width: self.ihdr.width,
+                height: self.ihdr.height,
+                color_type: ColorType::Rgba,
+                bit_depth: 8,
+                pixels: self.canvas.clone(),
+            },
+        })
+    }
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let raw = self.frames.next()?;
+        Some(self.compose(raw))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::InterlaceMethod;
+    use crate::image::EncodeOptions;
+    use crate::png::Png;
+
+    fn control(sequence_number: u32, width: u32, height: u32, x_offset: u32, y_offset: u32) -> FrameControl {
+        FrameControl {
+            sequence_number,
+            width,
+            height,
+            x_offset,
+            y_offset,
+            delay_num: 1,
+            delay_den: 10,
+            dispose_op: DisposeOp::None,
+            blend_op: BlendOp::Source,
+        }
+    }
+
+    // 编码一块纯色 RGBA 图像，返回拼接后的压缩数据
+    fn solid(width: u32, height: u32, rgba: [u8; 4]) -> Vec<u8> {
+        let image = Image {
+            width,
+            height,
+            color_type: ColorType::Rgba,
+            bit_depth: 8,
+            pixels: rgba.repeat((width * height) as usize),
+        };
+        let png = Png::encode_image(&image, EncodeOptions::default()).unwrap();
+        png.chunks_by_type("IDAT").iter().flat_map(|c| c.data().to_vec()).collect()
+    }
+
+    fn ihdr() -> Ihdr {
+        Ihdr::new(4, 4, 8, ColorType::Rgba, InterlaceMethod::None).unwrap()
+    }
+
+    // 4x4 画布：第一帧为默认图像（红色），第二帧在 (1, 1) 处覆盖 2x2 的半透明蓝色
+    fn animated(second: FrameControl) -> Png {
+        Png::from_chunks(vec![
+            ihdr().to_chunk(),
+            AnimationControl { num_frames: 2, num_plays: 0 }.to_chunk(),
+            FrameControl { dispose_op: DisposeOp::Background, ..control(0, 4, 4, 0, 0) }.to_chunk(),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), solid(4, 4, [255, 0, 0, 255])),
+            second.to_chunk(),
+            FrameData { sequence_number: second.sequence_number + 1, data: solid(2, 2, [0, 0, 255, 128]) }.to_chunk(),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()),
+        ])
+    }
+
+    fn pixel(frame: &Frame, x: usize, y: usize) -> &[u8] {
+        let start = (y * frame.image.width as usize + x) * 4;
+        &frame.image.pixels[start..start + 4]
+    }
+
+    #[test]
+    fn test_chunk_round_trips() {
+        let animation = AnimationControl { num_frames: 3, num_plays: 2 };
+        assert_eq!(AnimationControl::try_from(&animation.to_chunk()).unwrap(), animation);
+        let frame = FrameControl { dispose_op: DisposeOp::Previous, blend_op: BlendOp::Over, ..control(5, 2, 3, 1, 1) };
+        assert_eq!(FrameControl::try_from(&frame.to_chunk()).unwrap(), frame);
+        let data = FrameData { sequence_number: 7, data: vec![1, 2, 3] };
+        assert_eq!(FrameData::try_from(&data.to_chunk()).unwrap(), data);
+        assert!(FrameControl::try_from(&animation.to_chunk()).is_err());
+    }
+
+    #[test]
+    fn test_frame_control_validation() {
+        assert!(control(0, 4, 4, 0, 0).validate(&ihdr()).is_ok());
+        assert!(control(0, 3, 2, 2, 0).validate(&ihdr()).is_err());
+        assert!(control(0, 0, 2, 0, 0).validate(&ihdr()).is_err());
+        assert_eq!(FrameControl { delay_den: 0, ..control(0, 1, 1, 0, 0) }.delay(), 0.01);
+    }
+
+    #[test]
+    fn test_sequence_numbers() {
+        let png = animated(control(1, 2, 2, 1, 1));
+        assert_eq!(png.frame_controls().unwrap().len(), 2);
+        let png = animated(control(2, 2, 2, 1, 1));
+        match png.frame_controls() {
+            Err(PngError::InvalidAnimation(message)) => assert!(message.contains("fcTL sequence number 2 out of order, expected 1")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_compose_dispose_background_and_blend_source() {
+        let frames: Vec<Frame> = animated(control(1, 2, 2, 1, 1)).frames().unwrap().map(Result::unwrap).collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(pixel(&frames[0], 1, 1), [255, 0, 0, 255]);
+        // 第一帧处置为背景，第二帧直接覆盖
+        assert_eq!(pixel(&frames[1], 0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&frames[1], 1, 1), [0, 0, 255, 128]);
+    }
+
+    #[test]
+    fn test_compose_blend_over() {
+        let png = Png::from_chunks(
+            animated(control(1, 2, 2, 1, 1))
+                .chunks()
+                .iter()
+                .map(|chunk| match FrameControl::try_from(chunk) {
+                    Ok(fctl) if fctl.sequence_number == 0 => FrameControl { dispose_op: DisposeOp::None, ..fctl }.to_chunk(),
+                    Ok(fctl) => FrameControl { blend_op: BlendOp::Over, ..fctl }.to_chunk(),
+                    Err(_) => chunk.clone(),
+                })
+                .collect(),
+        );
+        // 第一帧保留，第二帧的半透明蓝色叠加在红色之上
+        let frames: Vec<Frame> = png.frames().unwrap().map(Result::unwrap).collect();
+        assert_eq!(pixel(&frames[1], 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&frames[1], 2, 2), [127, 0, 128, 255]);
+    }
+
+    #[test]
+    fn test_frame_count_mismatch() {
+        let mut chunks = animated(control(1, 2, 2, 1, 1)).chunks().to_vec();
+        chunks[1] = AnimationControl { num_frames: 3, num_plays: 0 }.to_chunk();
+        assert!(matches!(Png::from_chunks(chunks).frames(), Err(PngError::InvalidAnimation(_))));
+    }
+
+    #[test]
+    fn test_oversized_canvas() {
+        let mut chunks = animated(control(1, 2, 2, 1, 1)).chunks().to_vec();
+        chunks[0] = Ihdr::new(100_000, 100_000, 8, ColorType::Rgba, InterlaceMethod::None).unwrap().to_chunk();
+        chunks[2] = FrameControl { dispose_op: DisposeOp::Background, ..control(0, 100_000, 100_000, 0, 0) }.to_chunk();
+        match Png::from_chunks(chunks).frames() {
+            Err(PngError::InvalidAnimation(message)) => assert!(message.contains("exceeds the limit")),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::chunk::{self, Chunk};
+use crate::chunk_type::ChunkType;
+use crate::codec::ChunkCodec;
+use crate::error::{PngError, Result};
+
+// 附件格式的版本
+const FORMAT_VERSION: u8 = 1;
+
+// 嵌入在 PNG 中的文件
+// 数据块格式：版本(1) + 文件名长度(2) + 文件名 + MIME 类型长度(2) + MIME 类型 + 文件大小(8) + CRC(4) + 文件内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub name: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    // 附件使用的数据块类型：辅助、私有、可安全复制
+    pub const CHUNK_TYPE: &'static str = "atCh";
+
+    // 创建附件，文件名只保留最后一级，防止提取时写到目标目录之外
+    pub fn new(name: &str, mime_type: &str, data: Vec<u8>) -> Result<Attachment> {
+        Ok(Attachment {
+            name: sanitize_name(name)?,
+            mime_type: mime_type.to_string(),
+            data,
+        })
+    }
+
+    // 读取文件并根据扩展名推断 MIME 类型
+    pub fn from_file(path: &Path) -> Result<Attachment> {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| PngError::InvalidAttachment(format!("Invalid file name: {}", path.display())))?;
+        let data = std::fs::read(path)?;
+        Attachment::new(name, guess_mime_type(name), data)
+    }
+
+    // 返回数据的 CRC-32 校验值
+    pub fn checksum(&self) -> u32 {
+        chunk::crc32(&self.data)
+    }
+
+    // 编码为附件数据块
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        Ok(Chunk::new(Self::chunk_type(), self.encode()?))
+    }
+}
+
+impl ChunkCodec for Attachment {
+    fn chunk_type() -> ChunkType {
+        ChunkType::from_str(Self::CHUNK_TYPE).unwrap()
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        let mut reader = FieldReader { data, position: 0 };
+        let version = reader.take(1)?[0];
+        if version != FORMAT_VERSION {
+            return Err(PngError::InvalidAttachment(format!("Unsupported attachment format version: {}", version)));
+        }
+        let name = reader.string()?;
+        let mime_type = reader.string()?;
+        let size = reader.take(8)?;
+        let size = u64::from_be_bytes([size[0], size[1], size[2], size[3], size[4], size[5], size[6], size[7]]);
+        let checksum = reader.take(4)?;
+        let checksum = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        let size = usize::try_from(size)
+            .map_err(|_| PngError::InvalidAttachment(format!("Attachment size {} is too large", size)))?;
+        let data = reader.take(size)?.to_vec();
+        let attachment = Attachment::new(&name, &mime_type, data)?;
+        if attachment.checksum() != checksum {
+            return Err(PngError::InvalidAttachment(format!("Checksum mismatch for attachment {}", attachment.name)));
+        }
+        Ok(attachment)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let name = self.name.as_bytes();
+        let mime_type = self.mime_type.as_bytes();
+        if name.len() > u16::MAX as usize || mime_type.len() > u16::MAX as usize {
+            return Err(PngError::InvalidAttachment("Attachment name or MIME type is too long".to_string()));
+        }
+        let mut bytes = Vec::with_capacity(1 + 2 + name.len() + 2 + mime_type.len() + 12 + self.data.len());
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(name);
+        bytes.extend_from_slice(&(mime_type.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(mime_type);
+        bytes.extend_from_slice(&(self.data.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.checksum().to_be_bytes());
+        bytes.extend_from_slice(&self.data);
+        if bytes.len() > i32::MAX as usize {
+            return Err(PngError::InvalidAttachment(format!("Attachment {} is too large for a single chunk", self.name)));
+        }
+        Ok(bytes)
+    }
+}
+
+// 按顺序读取附件数据中的各个字段
+struct FieldReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self.position.checked_add(length).filter(|&end| end <= self.data.len());
+        match end {
+            Some(end) => {
+                let field = &self.data[self.position..end];
+                self.position = end;
+                Ok(field)
+            }
+            None => Err(PngError::InvalidAttachment("Attachment data is truncated".to_string())),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let length = self.take(2)?;
+        let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+        let bytes = self.take(length)?;
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+impl TryFrom<&Chunk> for Attachment {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        if chunk.chunk_type().to_string() != Self::CHUNK_TYPE {
+            return Err(PngError::InvalidAttachment(format!("Expected {} chunk, found {}", Self::CHUNK_TYPE, chunk.chunk_type())));
+        }
+        Attachment::decode(chunk.data())
+    }
+}
+
+impl fmt::Display for Attachment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}, {} bytes, crc {:#010x})", self.name, self.mime_type, self.data.len(), self.checksum())
+    }
+}
+
+// 只保留路径的最后一级，拒绝空名称和特殊目录名
+fn sanitize_name(name: &str) -> Result<String> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or("");
+    if base.is_empty() || base == "." || base == ".." {
+        return Err(PngError::InvalidAttachment(format!("Invalid attachment name: {:?}", name)));
+    }
+    Ok(base.to_string())
+}
+
+// 根据扩展名推断常见文件的 MIME 类型
+pub fn guess_mime_type(name: &str) -> &'static str {
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("txt") => "text/plain",
+        Some("md") => "text/markdown",
+        Some("html") | Some("htm") => "text/html",
+        Some("csv") => "text/csv",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("gz") => "application/gzip",
+        Some("tar") => "application/x-tar",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_round_trip() {
+        let attachment = Attachment::new("report.pdf", "application/pdf", b"%PDF-1.7".to_vec()).unwrap();
+        let chunk = attachment.to_chunk().unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "atCh");
+        assert!(chunk.chunk_type().is_valid());
+        assert_eq!(Attachment::try_from(&chunk).unwrap(), attachment);
+    }
+
+    #[test]
+    fn test_attachment_sanitizes_name() {
+        let attachment = Attachment::new("../../etc/passwd", "text/plain", vec![]).unwrap();
+        assert_eq!(attachment.name, "passwd");
+        assert!(Attachment::new("dir/..", "text/plain", vec![]).is_err());
+        assert!(Attachment::new("", "text/plain", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_attachment_checksum_mismatch() {
+        let attachment = Attachment::new("a.txt", "text/plain", b"hello".to_vec()).unwrap();
+        let chunk = attachment.to_chunk().unwrap();
+        let mut data = chunk.data().to_vec();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        let corrupted = Chunk::new(*chunk.chunk_type(), data);
+        assert!(Attachment::try_from(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_attachment_truncated() {
+        let attachment = Attachment::new("a.txt", "text/plain", b"hello".to_vec()).unwrap();
+        let chunk = attachment.to_chunk().unwrap();
+        let truncated = Chunk::new(*chunk.chunk_type(), chunk.data()[..chunk.data().len() - 2].to_vec());
+        assert!(Attachment::try_from(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_guess_mime_type() {
+        assert_eq!(guess_mime_type("backup.TAR"), "application/x-tar");
+        assert_eq!(guess_mime_type("notes.txt"), "text/plain");
+        assert_eq!(guess_mime_type("noextension"), "application/octet-stream");
+    }
+}
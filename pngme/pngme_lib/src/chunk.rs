@@ -1,10 +1,13 @@
 use std::convert::TryFrom;
 use std::fmt;
-use anyhow::{Result, Error};
+use std::io::{self, Write};
 use crc::{Crc, Algorithm};
 
 // 引入之前实现的 ChunkType 结构体
+use crate::chunk_ref::ChunkRef;
 use crate::chunk_type::ChunkType;
+use crate::crypto;
+use crate::error::{PngError, Result};
 
 // 定义 PNG 数据块的 CRC 多项式
 const CRC_32_POLY: u32 = 0x04C11DB7;
@@ -19,8 +22,22 @@ const CRC_32_ALGO: Algorithm<u32> = Algorithm {
   width: 32,
 };
 
+// 计算数据块类型与数据的 CRC 值
+pub(crate) fn checksum(chunk_type: &ChunkType, data: &[u8]) -> u32 {
+    let crc_calculator = Crc::<u32>::new(&CRC_32_ALGO);
+    let mut digest = crc_calculator.digest();
+    digest.update(&chunk_type.bytes());
+    digest.update(data);
+    digest.finalize()
+}
+
+// 计算任意数据的 CRC-32 值
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    Crc::<u32>::new(&CRC_32_ALGO).checksum(data)
+}
+
 // 定义 Chunk 结构体
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Chunk {
     length: u32,
     chunk_type: ChunkType,
@@ -32,11 +49,7 @@ impl Chunk {
     // 创建新的 Chunk 实例
     pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
         let length = data.len() as u32;
-        let mut all_data = Vec::new();
-        all_data.extend_from_slice(&chunk_type.bytes());
-        all_data.extend_from_slice(&data);
-        let crc_calculator = Crc::<u32>::new(&CRC_32_ALGO);
-        let crc = crc_calculator.checksum(&all_data);
+        let crc = checksum(&chunk_type, &data);
         Chunk {
             length,
             chunk_type,
@@ -45,6 +58,26 @@ impl Chunk {
         }
     }
 
+    // 原样保留给定的 CRC 组装数据块，不做校验
+    pub(crate) fn from_raw(chunk_type: ChunkType, data: Vec<u8>, crc: u32) -> Chunk {
+        Chunk {
+            length: data.len() as u32,
+            chunk_type,
+            data,
+            crc,
+        }
+    }
+
+    // 用口令加密数据后创建数据块
+    pub fn new_encrypted(chunk_type: ChunkType, data: &[u8], passphrase: &str) -> Result<Chunk> {
+        Ok(Chunk::new(chunk_type, crypto::encrypt(data, passphrase)?))
+    }
+
+    // 用口令解密数据块的数据
+    pub fn decrypt_data(&self, passphrase: &str) -> Result<Vec<u8>> {
+        crypto::decrypt(&self.data, passphrase)
+    }
+
     // 返回数据块数据的长度
     pub fn length(&self) -> u32 {
         self.length
@@ -65,9 +98,22 @@ impl Chunk {
         self.crc
     }
 
+    // 存储的 CRC 是否与按数据重新计算的一致
+    pub fn has_valid_crc(&self) -> bool {
+        checksum(&self.chunk_type, &self.data) == self.crc
+    }
+
     // 尝试将数据块的数据转换为字符串
     pub fn data_as_string(&self) -> Result<String> {
-        String::from_utf8(self.data.clone()).map_err(|e| Error::msg(e.to_string()))
+        Ok(String::from_utf8(self.data.clone())?)
+    }
+
+    // 将整个数据块直接写入目标，不额外分配缓冲区
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.length.to_be_bytes())?;
+        writer.write_all(&self.chunk_type.bytes())?;
+        writer.write_all(&self.data)?;
+        writer.write_all(&self.crc.to_be_bytes())
     }
 
     // 将整个数据块转换为字节序列
@@ -83,44 +129,17 @@ impl Chunk {
 
 // 实现从字节切片转换为 Chunk 实例的功能
 impl TryFrom<&[u8]> for Chunk {
-    type Error = Error;
+    type Error = PngError;
 
     fn try_from(value: &[u8]) -> Result<Self> {
-        if value.len() < 12 {
-            return Err(Error::msg("Input bytes are too short to form a valid chunk"));
-        }
-        let length = u32::from_be_bytes([value[0], value[1], value[2], value[3]]);
-        let chunk_type = ChunkType::try_from([value[4], value[5], value[6], value[7]])?;
-        let data_end = 8 + length as usize;
-        if value.len() < data_end + 4 {
-            return Err(Error::msg("Input bytes do not contain enough data for the specified length"));
-        }
-        let data = value[8..data_end].to_vec();
-        let expected_crc = u32::from_be_bytes([value[data_end], value[data_end + 1], value[data_end + 2], value[data_end + 3]]);
-        let mut all_data = Vec::new();
-        all_data.extend_from_slice(&chunk_type.bytes());
-        all_data.extend_from_slice(&data);
-        let crc_calculator = Crc::<u32>::new(&CRC_32_ALGO);
-        let calculated_crc = crc_calculator.checksum(&all_data);
-        if calculated_crc != expected_crc {
-            return Err(Error::msg("CRC check failed"));
-        }
-        Ok(Chunk {
-            length,
-            chunk_type,
-            data,
-            crc: calculated_crc,
-        })
+        Ok(ChunkRef::parse(value, 0)?.to_chunk())
     }
 }
 
 // 实现 Display 特性，用于格式化输出 Chunk 实例
 impl fmt::Display for Chunk {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.data_as_string() {
-            Ok(s) => write!(f, "Chunk {{ length: {}, type: {}, data: \"{}\", crc: {} }}", self.length, self.chunk_type, s, self.crc),
-            Err(_) => write!(f, "Chunk {{ length: {}, type: {}, data: {:?}, crc: {} }}", self.length, self.chunk_type, self.data, self.crc),
-        }
+        ChunkRef::from(self).fmt(f)
     }
 }
 
@@ -137,7 +156,7 @@ mod tests {
         let data = vec![82, 117, 115, 116];
         let chunk = Chunk::new(chunk_type, data);
         assert_eq!(chunk.length(), 4);
-        assert_eq!(chunk.crc(), 2882656334);
+        assert_eq!(chunk.crc(), 712841866);
     }
 
     #[test]
@@ -160,7 +179,7 @@ mod tests {
     fn test_chunk_data() {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
         let data = vec![82, 117, 115, 116];
-        let chunk = Chunk::new(chunk_type, data);
+        let chunk = Chunk::new(chunk_type, data.clone());
         assert_eq!(chunk.data(), data.as_slice());
     }
 
@@ -169,7 +188,7 @@ mod tests {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
         let data = vec![82, 117, 115, 116];
         let chunk = Chunk::new(chunk_type, data);
-        assert_eq!(chunk.crc(), 2882656334);
+        assert_eq!(chunk.crc(), 712841866);
     }
 
     #[test]
@@ -181,7 +200,7 @@ mod tests {
             let mut bytes = Vec::new();
             bytes.extend_from_slice(&chunk_type.bytes());
             bytes.extend_from_slice(&data);
-            Crc::<u32>::new(CRC_32_POLY).checksum(&bytes)
+            Crc::<u32>::new(&CRC_32_ALGO).checksum(&bytes)
         };
 
         let chunk_bytes = {
@@ -210,7 +229,7 @@ mod tests {
             let mut bytes = Vec::new();
             bytes.extend_from_slice(&chunk_type.bytes());
             bytes.extend_from_slice(&data);
-            Crc::<u32>::new(CRC_32_POLY).checksum(&bytes)
+            Crc::<u32>::new(&CRC_32_ALGO).checksum(&bytes)
         };
 
         let chunk_bytes = {
@@ -226,6 +245,32 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    fn test_crc_mismatch_reports_values() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let expected = checksum(&chunk_type, b"data");
+        let mut bytes = Chunk::new(chunk_type, b"data".to_vec()).as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        match Chunk::try_from(bytes.as_slice()) {
+            Err(PngError::CrcMismatch { offset, expected: stored, actual, .. }) => {
+                assert_eq!(offset, 0);
+                assert_eq!(stored, expected ^ 1);
+                assert_eq!(actual, expected);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_chunk() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let chunk = Chunk::new_encrypted(chunk_type, b"This is where your secret message will be!", "pass").unwrap();
+        assert!(!chunk.data().windows(6).any(|w| w == b"secret"));
+        assert_eq!(chunk.decrypt_data("pass").unwrap(), b"This is where your secret message will be!");
+        assert!(chunk.decrypt_data("wrong").is_err());
+    }
+
     #[test]
     fn test_chunk_trait_impls() {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
@@ -235,7 +280,7 @@ mod tests {
 
         let expected_chunk_string = format!(
             "Chunk {{ length: {}, type: {}, data: \"{}\", crc: {} }}",
-            4, "RuSt", "Rust", 2882656334
+            4, "RuSt", "Rust", 712841866u32
         );
         assert_eq!(chunk_string, expected_chunk_string);
     }
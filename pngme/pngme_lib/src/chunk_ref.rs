@@ -0,0 +1,167 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::chunk::{self, Chunk};
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+
+// 借用输入缓冲区的数据块视图，数据部分不做任何复制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkRef<'a> {
+    chunk_type: ChunkType,
+    data: &'a [u8],
+    crc: u32,
+}
+
+impl<'a> ChunkRef<'a> {
+    // 解析 bytes 开头的一个数据块并校验 CRC；offset 为 bytes 在整个输入中的起始位置，只用于错误信息
+    pub(crate) fn parse(bytes: &'a [u8], offset: u64) -> Result<ChunkRef<'a>> {
+        let truncated = |needed: usize| PngError::Truncated {
+            offset: offset + bytes.len() as u64,
+            needed: (needed - bytes.len()) as u64,
+        };
+        if bytes.len() < 12 {
+            return Err(truncated(12));
+        }
+        let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        let chunk_type = ChunkType::try_from([bytes[4], bytes[5], bytes[6], bytes[7]])?;
+        let data_end = 8 + length;
+        if bytes.len() < data_end + 4 {
+            return Err(truncated(data_end + 4));
+        }
+        let data = &bytes[8..data_end];
+        let expected = u32::from_be_bytes([bytes[data_end], bytes[data_end + 1], bytes[data_end + 2], bytes[data_end + 3]]);
+        let actual = chunk::checksum(&chunk_type, data);
+        if actual != expected {
+            return Err(PngError::CrcMismatch {
+                chunk_type,
+                offset,
+                expected,
+                actual,
+            });
+        }
+        Ok(ChunkRef {
+            chunk_type,
+            data,
+            crc: expected,
+        })
+    }
+
+    // 返回数据块数据的长度
+    pub fn length(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    // 返回数据块类型的引用
+    pub fn chunk_type(&self) -> &ChunkType {
+        &self.chunk_type
+    }
+
+    // 返回借用的数据，生命周期与输入缓冲区相同
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    // 返回数据块的 CRC 值
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    // 存储的 CRC 是否与按数据重新计算的一致
+    pub fn has_valid_crc(&self) -> bool {
+        chunk::checksum(&self.chunk_type, self.data) == self.crc
+    }
+
+    // 数据块在输入中占用的总字节数（长度、类型、数据和 CRC）
+    pub fn total_length(&self) -> usize {
+        12 + self.data.len()
+    }
+
+    // 尝试把数据解释为 UTF-8 字符串，不复制数据
+    pub fn data_as_str(&self) -> Result<&'a str, std::str::Utf8Error> {
+        std::str::from_utf8(self.data)
+    }
+
+    // 复制数据，转换为拥有所有权的数据块
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::from_raw(self.chunk_type, self.data.to_vec(), self.crc)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for ChunkRef<'a> {
+    type Error = PngError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self> {
+        ChunkRef::parse(value, 0)
+    }
+}
+
+impl<'a> From<&'a Chunk> for ChunkRef<'a> {
+    fn from(chunk: &'a Chunk) -> Self {
+        ChunkRef {
+            chunk_type: *chunk.chunk_type(),
+            data: chunk.data(),
+            crc: chunk.crc(),
+        }
+    }
+}
+
+impl From<ChunkRef<'_>> for Chunk {
+    fn from(chunk: ChunkRef<'_>) -> Self {
+        chunk.to_chunk()
+    }
+}
+
+// 与 Chunk 的输出格式相同
+impl fmt::Display for ChunkRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.data_as_str() {
+            Ok(s) => write!(f, "Chunk {{ length: {}, type: {}, data: \"{}\", crc: {} }}", self.length(), self.chunk_type, s, self.crc),
+            Err(_) => write!(f, "Chunk {{ length: {}, type: {}, data: {:?}, crc: {} }}", self.length(), self.chunk_type, self.data, self.crc),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn testing_chunk() -> Chunk {
+        Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"This is where your secret message will be!".to_vec())
+    }
+
+    #[test]
+    fn test_chunk_ref_borrows_input() {
+        let bytes = testing_chunk().as_bytes();
+        let chunk = ChunkRef::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(chunk.length(), 42);
+        assert_eq!(chunk.chunk_type().to_string(), "RuSt");
+        assert_eq!(chunk.data().as_ptr(), bytes[8..].as_ptr());
+        assert_eq!(chunk.data_as_str().unwrap(), "This is where your secret message will be!");
+        assert_eq!(chunk.total_length(), bytes.len());
+    }
+
+    #[test]
+    fn test_chunk_ref_to_chunk() {
+        let owned = testing_chunk();
+        let bytes = owned.as_bytes();
+        let chunk = ChunkRef::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(chunk.to_chunk(), owned);
+        assert_eq!(ChunkRef::from(&owned), chunk);
+        assert_eq!(chunk.to_string(), owned.to_string());
+    }
+
+    #[test]
+    fn test_chunk_ref_errors() {
+        let mut bytes = testing_chunk().as_bytes();
+        assert!(matches!(
+            ChunkRef::parse(&bytes[..20], 100),
+            Err(PngError::Truncated { offset: 120, needed: 34 })
+        ));
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(matches!(ChunkRef::parse(&bytes, 100), Err(PngError::CrcMismatch { offset: 100, .. })));
+    }
+}
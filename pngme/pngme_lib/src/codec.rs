@@ -0,0 +1,154 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ancillary::KnownChunk;
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::error::Result;
+use crate::palette::Palette;
+
+// 自定义数据块类型的编解码器，实现后可通过 Png::decode_chunk 等方法按类型读写数据块
+pub trait ChunkCodec: Sized {
+    // 编解码的数据块类型
+    fn chunk_type() -> ChunkType;
+
+    // 从数据块数据解码
+    fn decode(data: &[u8]) -> Result<Self>;
+
+    // 编码为数据块数据
+    fn encode(&self) -> Result<Vec<u8>>;
+}
+
+// 把数据块数据解码并格式化为一行文本
+type Describe = Box<dyn Fn(&[u8]) -> Result<String> + Send + Sync>;
+
+// 数据块类型到编解码器的注册表，用于按字段含义显示数据块
+#[derive(Default)]
+pub struct ChunkRegistry {
+    entries: Vec<(ChunkType, Describe)>,
+}
+
+impl ChunkRegistry {
+    // 创建空的注册表
+    pub fn new() -> ChunkRegistry {
+        ChunkRegistry::default()
+    }
+
+    // 创建已注册 PLTE 以及 KnownChunk 中全部标准辅助数据块的注册表
+    pub fn standard() -> ChunkRegistry {
+        let mut registry = ChunkRegistry::new();
+        registry.register::<Palette>();
+        for name in KnownChunk::CHUNK_TYPES {
+            registry.insert(
+                ChunkType::from_str(name).unwrap(),
+                Box::new(move |data| KnownChunk::parse(name, data).map(|known| known.to_string())),
+            );
+        }
+        registry
+    }
+
+    // 注册 T 的编解码器，输出格式为 "类型: T 的 Display"，同类型已注册时替换原有的编解码器
+    pub fn register<T: ChunkCodec + fmt::Display + 'static>(&mut self) -> &mut ChunkRegistry {
+        let chunk_type = T::chunk_type();
+        let name = chunk_type.to_string();
+        self.insert(chunk_type, Box::new(move |data| T::decode(data).map(|value| format!("{}: {}", name, value))));
+        self
+    }
+
+    fn insert(&mut self, chunk_type: ChunkType, describe: Describe) {
+        self.entries.retain(|(registered, _)| *registered != chunk_type);
+        self.entries.push((chunk_type, describe));
+    }
+
+    // 数据块类型是否已注册
+    pub fn is_registered(&self, chunk_type: &ChunkType) -> bool {
+        self.entries.iter().any(|(registered, _)| registered == chunk_type)
+    }
+
+    // 按注册顺序返回所有已注册的数据块类型
+    pub fn chunk_types(&self) -> impl Iterator<Item = &ChunkType> {
+        self.entries.iter().map(|(chunk_type, _)| chunk_type)
+    }
+
+    // 用注册的编解码器解码并格式化数据块，类型未注册时返回 None
+    pub fn describe(&self, chunk: &ChunkRef) -> Option<Result<String>> {
+        self.entries
+            .iter()
+            .find(|(registered, _)| registered == chunk.chunk_type())
+            .map(|(_, describe)| describe(chunk.data()))
+    }
+}
+
+impl fmt::Debug for ChunkRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.chunk_types().map(ToString::to_string)).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::error::PngError;
+
+    // 测试用的私有数据块：两个大端 u16 表示的版本号
+    #[derive(Debug, PartialEq)]
+    struct Version {
+        major: u16,
+        minor: u16,
+    }
+
+    impl ChunkCodec for Version {
+        fn chunk_type() -> ChunkType {
+            ChunkType::from_str("veRs").unwrap()
+        }
+
+        fn decode(data: &[u8]) -> Result<Self> {
+            if data.len() != 4 {
+                return Err(PngError::InvalidPayload(format!("Invalid veRs length: {}", data.len())));
+            }
+            Ok(Version {
+                major: u16::from_be_bytes([data[0], data[1]]),
+                minor: u16::from_be_bytes([data[2], data[3]]),
+            })
+        }
+
+        fn encode(&self) -> Result<Vec<u8>> {
+            Ok([self.major.to_be_bytes(), self.minor.to_be_bytes()].concat())
+        }
+    }
+
+    impl fmt::Display for Version {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "v{}.{}", self.major, self.minor)
+        }
+    }
+
+    fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+    }
+
+    #[test]
+    fn test_register_and_describe() {
+        let mut registry = ChunkRegistry::new();
+        registry.register::<Version>();
+        assert!(registry.is_registered(&Version::chunk_type()));
+        let data = Version { major: 1, minor: 2 }.encode().unwrap();
+        let described = registry.describe(&ChunkRef::from(&chunk("veRs", &data)));
+        assert_eq!(described.unwrap().unwrap(), "veRs: v1.2");
+        assert!(registry.describe(&ChunkRef::from(&chunk("veRs", &[1]))).unwrap().is_err());
+        assert!(registry.describe(&ChunkRef::from(&chunk("ruSt", &data))).is_none());
+    }
+
+    #[test]
+    fn test_standard_registry() {
+        let mut registry = ChunkRegistry::standard();
+        assert_eq!(registry.chunk_types().count(), KnownChunk::CHUNK_TYPES.len() + 1);
+        let phys = chunk("pHYs", &[0, 0, 0x0B, 0x13, 0, 0, 0x0B, 0x13, 1]);
+        assert_eq!(registry.describe(&ChunkRef::from(&phys)).unwrap().unwrap(), "pHYs: 2835x2835 px/m (72 DPI)");
+        // 重复注册同一类型时替换而不是追加
+        registry.register::<Version>().register::<Version>();
+        assert_eq!(registry.chunk_types().count(), KnownChunk::CHUNK_TYPES.len() + 2);
+    }
+}
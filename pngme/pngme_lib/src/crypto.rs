@@ -0,0 +1,191 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::error::{PngError, Result};
+
+// 加密数据的格式版本
+const FORMAT_VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+// 头部：版本(1) + Argon2 参数 m/t/p(各 4 字节) + 盐 + 随机数
+const HEADER_LENGTH: usize = 1 + 12 + SALT_LENGTH + NONCE_LENGTH;
+// 解密时允许的最大内存开销（KiB），防止恶意文件耗尽内存
+const MAX_MEMORY_COST: u32 = 1 << 20;
+// 解密时允许的最大迭代次数和并行度，防止恶意文件占用过多 CPU 和线程
+const MAX_TIME_COST: u32 = 64;
+const MAX_PARALLELISM: u32 = 64;
+
+// 由口令派生密钥所用的 Argon2id 参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    // 内存开销，单位 KiB
+    pub memory_cost: u32,
+    // 迭代次数
+    pub time_cost: u32,
+    // 并行度
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_cost: Params::DEFAULT_M_COST,
+            time_cost: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+// 用 Argon2id 从口令和盐派生 32 字节密钥
+fn derive_key(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<Key> {
+    let params = Params::new(params.memory_cost, params.time_cost, params.parallelism, Some(32))
+        .map_err(|e| PngError::Crypto(format!("Invalid key derivation parameters: {}", e)))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let mut key = Key::default();
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| PngError::Crypto(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+// 使用默认参数加密数据
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    encrypt_with_params(plaintext, passphrase, KdfParams::default())
+}
+
+// 用口令加密数据，输出带版本号、KDF 参数、盐和随机数头部的密文；口令不能为空
+pub fn encrypt_with_params(plaintext: &[u8], passphrase: &str, params: KdfParams) -> Result<Vec<u8>> {
+    if passphrase.is_empty() {
+        return Err(PngError::Crypto("Passphrase must not be empty".to_string()));
+    }
+    let mut salt = [0u8; SALT_LENGTH];
+    let mut nonce = [0u8; NONCE_LENGTH];
+    getrandom::getrandom(&mut salt).map_err(|e| PngError::Crypto(e.to_string()))?;
+    getrandom::getrandom(&mut nonce).map_err(|e| PngError::Crypto(e.to_string()))?;
+
+    let key = derive_key(passphrase, &salt, params)?;
+    let cipher = ChaCha20Poly1305::new(&key);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| PngError::Crypto("Encryption failed".to_string()))?;
+
+    let mut payload = Vec::with_capacity(HEADER_LENGTH + ciphertext.len());
+    payload.push(FORMAT_VERSION);
+    payload.extend_from_slice(&params.memory_cost.to_be_bytes());
+    payload.extend_from_slice(&params.time_cost.to_be_bytes());
+    payload.extend_from_slice(&params.parallelism.to_be_bytes());
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+    Ok(payload)
+}
+
+// 解析头部并解密，口令错误或数据被篡改时返回错误
+pub fn decrypt(payload: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    if payload.len() < HEADER_LENGTH {
+        return Err(PngError::Crypto("Encrypted payload is too short".to_string()));
+    }
+    if payload[0] != FORMAT_VERSION {
+        return Err(PngError::Crypto(format!("Unsupported encryption format version: {}", payload[0])));
+    }
+    let read_u32 = |i: usize| u32::from_be_bytes([payload[i], payload[i + 1], payload[i + 2], payload[i + 3]]);
+    let params = KdfParams {
+        memory_cost: read_u32(1),
+        time_cost: read_u32(5),
+        parallelism: read_u32(9),
+    };
+    if params.memory_cost > MAX_MEMORY_COST {
+        return Err(PngError::Crypto(format!("Key derivation memory cost too large: {} KiB", params.memory_cost)));
+    }
+    if params.time_cost > MAX_TIME_COST {
+        return Err(PngError::Crypto(format!("Key derivation time cost too large: {}", params.time_cost)));
+    }
+    if params.parallelism > MAX_PARALLELISM {
+        return Err(PngError::Crypto(format!("Key derivation parallelism too large: {}", params.parallelism)));
+    }
+    let salt = &payload[13..13 + SALT_LENGTH];
+    let nonce = &payload[13 + SALT_LENGTH..HEADER_LENGTH];
+
+    let key = derive_key(passphrase, salt, params)?;
+    let cipher = ChaCha20Poly1305::new(&key);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), &payload[HEADER_LENGTH..])
+        .map_err(|_| PngError::Crypto("Decryption failed: wrong passphrase or corrupted data".to_string()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试中使用较小的参数以加快速度
+    const FAST: KdfParams = KdfParams {
+        memory_cost: 64,
+        time_cost: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let payload = encrypt_with_params(b"Your secret message", "hunter2", FAST).unwrap();
+        assert_eq!(payload[0], FORMAT_VERSION);
+        assert_eq!(payload.len(), HEADER_LENGTH + 19 + 16);
+        assert_eq!(decrypt(&payload, "hunter2").unwrap(), b"Your secret message");
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let payload = encrypt_with_params(b"secret", "hunter2", FAST).unwrap();
+        assert!(decrypt(&payload, "hunter3").is_err());
+    }
+
+    #[test]
+    fn test_empty_passphrase() {
+        match encrypt_with_params(b"secret", "", FAST) {
+            Err(PngError::Crypto(message)) => assert_eq!(message, "Passphrase must not be empty"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tampered_payload() {
+        let mut payload = encrypt_with_params(b"secret", "hunter2", FAST).unwrap();
+        let last = payload.len() - 1;
+        payload[last] ^= 1;
+        assert!(decrypt(&payload, "hunter2").is_err());
+    }
+
+    #[test]
+    fn test_random_salt_and_nonce() {
+        let a = encrypt_with_params(b"secret", "hunter2", FAST).unwrap();
+        let b = encrypt_with_params(b"secret", "hunter2", FAST).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert!(decrypt(&[FORMAT_VERSION; 10], "hunter2").is_err());
+        let mut payload = encrypt_with_params(b"secret", "hunter2", FAST).unwrap();
+        payload[0] = 9;
+        assert!(decrypt(&payload, "hunter2").is_err());
+    }
+
+    #[test]
+    fn test_excessive_params() {
+        for params in [
+            KdfParams { memory_cost: MAX_MEMORY_COST + 1, ..FAST },
+            KdfParams { time_cost: MAX_TIME_COST + 1, ..FAST },
+            KdfParams { parallelism: MAX_PARALLELISM + 1, ..FAST },
+        ] {
+            let mut payload = encrypt_with_params(b"secret", "hunter2", FAST).unwrap();
+            payload[1..5].copy_from_slice(&params.memory_cost.to_be_bytes());
+            payload[5..9].copy_from_slice(&params.time_cost.to_be_bytes());
+            payload[9..13].copy_from_slice(&params.parallelism.to_be_bytes());
+            match decrypt(&payload, "hunter2") {
+                Err(PngError::Crypto(message)) => assert!(message.contains("too large")),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+}
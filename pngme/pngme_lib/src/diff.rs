@@ -0,0 +1,333 @@
+use std::fmt;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::text::TextEntry;
+
+// 最长公共子序列比较表的最大大小，超过时只匹配公共前缀和后缀
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+// 数据中的一段差异：old 从 old_offset 开始的字节被替换为 new 从 new_offset 开始的字节
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteDiff {
+    pub old_offset: usize,
+    pub old: Vec<u8>,
+    pub new_offset: usize,
+    pub new: Vec<u8>,
+}
+
+// 两个 PNG 之间一个数据块的变化；下标分别指向原文件和新文件中的数据块
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkChange {
+    // 只在新文件中出现
+    Added { index: usize, chunk_type: ChunkType, length: u32 },
+    // 只在原文件中出现
+    Removed { index: usize, chunk_type: ChunkType, length: u32 },
+    // 内容相同但相对顺序改变
+    Moved { chunk_type: ChunkType, from: usize, to: usize },
+    // 同类型数据块的内容改变；文本数据块附带解码后 "关键字\0文本" 的逐字节差异
+    Modified {
+        chunk_type: ChunkType,
+        old_index: usize,
+        new_index: usize,
+        old_length: u32,
+        new_length: u32,
+        differences: Vec<ByteDiff>,
+    },
+}
+
+impl ChunkChange {
+    // 排序用的位置：优先使用新文件中的下标
+    fn position(&self) -> usize {
+        match self {
+            ChunkChange::Added { index, .. } | ChunkChange::Removed { index, .. } => *index,
+            ChunkChange::Moved { to, .. } => *to,
+            ChunkChange::Modified { new_index, .. } => *new_index,
+        }
+    }
+}
+
+impl fmt::Display for ChunkChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkChange::Added { index, chunk_type, length } => {
+                write!(f, "+ {} chunk {} ({} bytes)", chunk_type, index, length)
+            }
+            ChunkChange::Removed { index, chunk_type, length } => {
+                write!(f, "- {} chunk {} ({} bytes)", chunk_type, index, length)
+            }
+            ChunkChange::Moved { chunk_type, from, to } => write!(f, "~ {} chunk moved from {} to {}", chunk_type, from, to),
+            ChunkChange::Modified { chunk_type, old_index, new_index, old_length, new_length, differences } => {
+                write!(
+                    f,
+                    "M {} chunk {} -> {} ({} -> {} bytes)",
+                    chunk_type, old_index, new_index, old_length, new_length
+                )?;
+                for diff in differences {
+                    write!(
+                        f,
+                        "\n    @{}: {:?} -> {:?}",
+                        diff.old_offset,
+                        String::from_utf8_lossy(&diff.old),
+                        String::from_utf8_lossy(&diff.new)
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// 最长公共子序列，返回按顺序匹配的下标对；先去掉公共前缀和后缀以缩小比较表
+// 剩余部分过大时不再比较，只返回公共前缀和后缀
+fn lcs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mut middle_a, mut middle_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    if (middle_a.len() + 1).saturating_mul(middle_b.len() + 1) > MAX_LCS_CELLS {
+        middle_a = &[];
+        middle_b = &[];
+    }
+
+    let width = middle_b.len() + 1;
+    let mut table = vec![0u32; (middle_a.len() + 1) * width];
+    for i in (0..middle_a.len()).rev() {
+        for j in (0..middle_b.len()).rev() {
+            table[i * width + j] = if middle_a[i] == middle_b[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < middle_a.len() && j < middle_b.len() {
+        if middle_a[i] == middle_b[j] {
+            pairs.push((prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs.extend((0..suffix).map(|k| (a.len() - suffix + k, b.len() - suffix + k)));
+    pairs
+}
+
+// 逐字节比较两段数据，把匹配之间的空隙合并为差异区间
+fn byte_diff(old: &[u8], new: &[u8]) -> Vec<ByteDiff> {
+    let mut differences = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in lcs(old, new).into_iter().chain(std::iter::once((old.len(), new.len()))) {
+        if next_i > i || next_j > j {
+            differences.push(ByteDiff {
+                old_offset: i,
+                old: old[i..next_i].to_vec(),
+                new_offset: j,
+                new: new[j..next_j].to_vec(),
+            });
+        }
+        i = next_i + 1;
+        j = next_j + 1;
+    }
+    differences
+}
+
+// 文本数据块解码（zTXt 和压缩的 iTXt 先解压）后的 "关键字\0文本"，用于逐字节比较
+// 其他类型或解码失败的数据块返回 None，按二进制处理
+fn text_bytes(chunk: &Chunk) -> Option<Vec<u8>> {
+    if !TextEntry::CHUNK_TYPES.contains(&chunk.chunk_type().to_string().as_str()) {
+        return None;
+    }
+    let entry = TextEntry::try_from(chunk).ok()?;
+    Some(format!("{}\0{}", entry.keyword(), entry.text()).into_bytes())
+}
+
+// 对齐两个数据块序列并报告变化：
+// 内容完全相同且顺序一致的数据块视为未变化，其余内容相同的视为移动，
+// 剩下的同类型数据块按出现顺序配对为修改，无法配对的视为新增或删除
+pub(crate) fn diff(old: &[Chunk], new: &[Chunk]) -> Vec<ChunkChange> {
+    let pairs = lcs(old, new);
+    let mut old_matched = vec![false; old.len()];
+    let mut new_matched = vec![false; new.len()];
+    for &(i, j) in &pairs {
+        old_matched[i] = true;
+        new_matched[j] = true;
+    }
+
+    let mut changes = Vec::new();
+    for i in 0..old.len() {
+        if old_matched[i] {
+            continue;
+        }
+        if let Some(j) = (0..new.len()).find(|&j| !new_matched[j] && new[j] == old[i]) {
+            old_matched[i] = true;
+            new_matched[j] = true;
+            changes.push(ChunkChange::Moved {
+                chunk_type: *old[i].chunk_type(),
+                from: i,
+                to: j,
+            });
+        }
+    }
+    for i in 0..old.len() {
+        if old_matched[i] {
+            continue;
+        }
+        let candidate = (0..new.len()).find(|&j| !new_matched[j] && new[j].chunk_type() == old[i].chunk_type());
+        if let Some(j) = candidate {
+            old_matched[i] = true;
+            new_matched[j] = true;
+            let differences = match (text_bytes(&old[i]), text_bytes(&new[j])) {
+                (Some(old_text), Some(new_text)) => byte_diff(&old_text, &new_text),
+                _ => Vec::new(),
+            };
+            changes.push(ChunkChange::Modified {
+                chunk_type: *old[i].chunk_type(),
+                old_index: i,
+                new_index: j,
+                old_length: old[i].length(),
+                new_length: new[j].length(),
+                differences,
+            });
+        }
+    }
+    for (i, chunk) in old.iter().enumerate().filter(|(i, _)| !old_matched[*i]) {
+        changes.push(ChunkChange::Removed {
+            index: i,
+            chunk_type: *chunk.chunk_type(),
+            length: chunk.length(),
+        });
+    }
+    for (j, chunk) in new.iter().enumerate().filter(|(j, _)| !new_matched[*j]) {
+        changes.push(ChunkChange::Added {
+            index: j,
+            chunk_type: *chunk.chunk_type(),
+            length: chunk.length(),
+        });
+    }
+    changes.sort_by_key(ChunkChange::position);
+    changes
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use crate::text::CompressedText;
+
+    fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+    }
+
+    fn base() -> Vec<Chunk> {
+        vec![
+            chunk("IHDR", &[0; 13]),
+            chunk("tEXt", b"Title\0Hello world"),
+            chunk("IDAT", &[1, 2, 3]),
+            chunk("IEND", &[]),
+        ]
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        assert!(diff(&base(), &base()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_added_and_removed() {
+        let mut new = base();
+        new.remove(1);
+        new.insert(2, chunk("tIME", &[0; 7]));
+        let changes = diff(&base(), &new);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(changes[0], ChunkChange::Removed { index: 1, .. }));
+        assert!(matches!(changes[1], ChunkChange::Added { index: 2, .. }));
+    }
+
+    #[test]
+    fn test_diff_moved() {
+        let mut new = base();
+        let text = new.remove(1);
+        new.insert(3, text);
+        let changes = diff(&base(), &new);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(changes[0], ChunkChange::Moved { from: 1, to: 3, .. }));
+    }
+
+    #[test]
+    fn test_diff_modified_text() {
+        let mut new = base();
+        new[1] = chunk("tEXt", b"Title\0Hello there world");
+        let changes = diff(&base(), &new);
+        match &changes[..] {
+            [ChunkChange::Modified { old_index: 1, new_index: 1, differences, .. }] => {
+                assert_eq!(
+                    differences,
+                    &vec![ByteDiff {
+                        old_offset: 12,
+                        old: vec![],
+                        new_offset: 12,
+                        new: b"there ".to_vec(),
+                    }]
+                );
+            }
+            other => panic!("unexpected changes: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_diff_modified_compressed_text() {
+        let compressed = |text: &str| CompressedText::new("Comment", text).unwrap().to_chunk().unwrap();
+        let old = vec![compressed("lorem ipsum dolor")];
+        let new = vec![compressed("lorem ipsum color")];
+        match &diff(&old, &new)[..] {
+            [ChunkChange::Modified { differences, .. }] => {
+                assert_eq!(differences.len(), 1);
+                assert_eq!(differences[0].old_offset, "Comment\0lorem ipsum ".len());
+                assert_eq!((&differences[0].old[..], &differences[0].new[..]), (&b"d"[..], &b"c"[..]));
+            }
+            other => panic!("unexpected changes: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_diff_utf8_binary_chunk() {
+        // 数据恰好是 UTF-8 的非文本数据块仍按二进制处理
+        let old = vec![chunk("ruSt", b"hello")];
+        let new = vec![chunk("ruSt", b"hallo")];
+        match &diff(&old, &new)[..] {
+            [ChunkChange::Modified { differences, .. }] => assert!(differences.is_empty()),
+            other => panic!("unexpected changes: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_diff_modified_binary() {
+        let mut new = base();
+        new[2] = chunk("IDAT", &[1, 2, 0xFF, 4]);
+        let changes = diff(&base(), &new);
+        match &changes[..] {
+            [ChunkChange::Modified { old_length: 3, new_length: 4, differences, .. }] => assert!(differences.is_empty()),
+            other => panic!("unexpected changes: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_byte_diff_multiple_ranges() {
+        let differences = byte_diff(b"abcdef", b"aXcdeY");
+        assert_eq!(differences.len(), 2);
+        assert_eq!((differences[0].old_offset, &differences[0].new[..]), (1, &b"X"[..]));
+        assert_eq!((differences[1].old_offset, &differences[1].old[..]), (5, &b"f"[..]));
+    }
+}
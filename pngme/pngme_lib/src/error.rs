@@ -0,0 +1,144 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::string::FromUtf8Error;
+
+use crate::chunk_type::ChunkType;
+
+// pngme_lib 中所有操作返回的错误类型
+// 偏移量均为相对于输入起点的绝对字节偏移
+#[derive(Debug)]
+pub enum PngError {
+    // 文件头不是标准 PNG 签名
+    InvalidSignature,
+    // 输入在 offset 处提前结束，至少还需要 needed 字节
+    Truncated { offset: u64, needed: u64 },
+    // 数据块 CRC 校验失败，offset 为该数据块的起始位置
+    CrcMismatch { chunk_type: ChunkType, offset: u64, expected: u32, actual: u32 },
+    // 数据块类型不是 4 个 ASCII 字母
+    InvalidChunkType(String),
+    // 找不到指定类型的数据块
+    ChunkNotFound(ChunkType),
+    // 数据块下标超出范围
+    IndexOutOfRange { index: usize, len: usize },
+    // IHDR 缺失或内容不符合规范
+    InvalidHeader(String),
+    // 像素数据、过滤类型、调色板等图像内容错误
+    InvalidImage(String),
+    // zlib 压缩或解压失败
+    Compression(String),
+    // 加密、解密或密钥派生失败
+    Crypto(String),
+    // 分段数据缺失、重复或校验失败
+    InvalidPayload(String),
+    // 附件数据格式错误
+    InvalidAttachment(String),
+    // 文本数据块的关键字、编码或格式错误
+    InvalidText(String),
+    // APNG 动画数据块缺失、序号错误或帧区域超出画布
+    InvalidAnimation(String),
+    // 标准辅助数据块（gAMA、pHYs 等）的长度或取值不符合规范
+    InvalidAncillary(String),
+    // PLTE 或 tRNS 的长度、颜色数或透明色与 IHDR 不符
+    InvalidPalette(String),
+    // 数据不是有效的 UTF-8
+    InvalidUtf8(FromUtf8Error),
+    // 底层读写错误
+    Io(io::Error),
+}
+
+// pngme_lib 使用的 Result 类型
+pub type Result<T, E = PngError> = std::result::Result<T, E>;
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PngError::InvalidSignature => write!(f, "Invalid PNG header"),
+            PngError::Truncated { offset, needed } => {
+                write!(f, "Unexpected end of input at offset {}: {} more bytes needed", offset, needed)
+            }
+            PngError::CrcMismatch { chunk_type, offset, expected, actual } => write!(
+                f,
+                "CRC check failed for {} chunk at offset {}: expected {:#010x}, computed {:#010x}",
+                chunk_type, offset, expected, actual
+            ),
+            PngError::InvalidChunkType(value) => {
+                write!(f, "Invalid chunk type {:?}: must be 4 ASCII alphabetic characters", value)
+            }
+            PngError::ChunkNotFound(chunk_type) => write!(f, "Chunk of type {} not found", chunk_type),
+            PngError::IndexOutOfRange { index, len } => {
+                write!(f, "Chunk index {} out of range for {} chunks", index, len)
+            }
+            PngError::InvalidHeader(message) => write!(f, "Invalid IHDR: {}", message),
+            PngError::InvalidImage(message) => write!(f, "Invalid image: {}", message),
+            PngError::Compression(message) => write!(f, "Compression error: {}", message),
+            PngError::Crypto(message) => write!(f, "{}", message),
+            PngError::InvalidPayload(message) => write!(f, "Invalid payload: {}", message),
+            PngError::InvalidAttachment(message) => write!(f, "Invalid attachment: {}", message),
+            PngError::InvalidText(message) => write!(f, "Invalid text chunk: {}", message),
+            PngError::InvalidAnimation(message) => write!(f, "Invalid animation: {}", message),
+            PngError::InvalidAncillary(message) => write!(f, "Invalid ancillary chunk: {}", message),
+            PngError::InvalidPalette(message) => write!(f, "Invalid palette: {}", message),
+            PngError::InvalidUtf8(e) => write!(f, "{}", e),
+            PngError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for PngError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PngError::InvalidUtf8(e) => Some(e),
+            PngError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PngError {
+    fn from(e: io::Error) -> Self {
+        PngError::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for PngError {
+    fn from(e: FromUtf8Error) -> Self {
+        PngError::InvalidUtf8(e)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_error_display() {
+        let error = PngError::CrcMismatch {
+            chunk_type: ChunkType::from_str("RuSt").unwrap(),
+            offset: 33,
+            expected: 1,
+            actual: 2,
+        };
+        assert_eq!(
+            error.to_string(),
+            "CRC check failed for RuSt chunk at offset 33: expected 0x00000001, computed 0x00000002"
+        );
+    }
+
+    #[test]
+    fn test_error_works_with_anyhow() {
+        fn fails() -> anyhow::Result<()> {
+            Err(PngError::InvalidSignature)?
+        }
+        let error = fails().unwrap_err();
+        assert!(matches!(error.downcast_ref::<PngError>(), Some(PngError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_io_error_source() {
+        let error = PngError::from(io::Error::other("disk on fire"));
+        assert!(error::Error::source(&error).is_some());
+    }
+}
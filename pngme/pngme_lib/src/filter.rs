@@ -0,0 +1,175 @@
+use std::convert::TryFrom;
+
+use crate::error::{PngError, Result};
+
+// PNG 规范定义的五种扫描行过滤类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    None,
+    Sub,
+    Up,
+    Average,
+    Paeth,
+}
+
+impl FilterType {
+    // 全部过滤类型，按编码值排序
+    pub const ALL: [FilterType; 5] = [
+        FilterType::None,
+        FilterType::Sub,
+        FilterType::Up,
+        FilterType::Average,
+        FilterType::Paeth,
+    ];
+
+    // 返回过滤类型在扫描行首字节中的编码值
+    pub fn value(&self) -> u8 {
+        match self {
+            FilterType::None => 0,
+            FilterType::Sub => 1,
+            FilterType::Up => 2,
+            FilterType::Average => 3,
+            FilterType::Paeth => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for FilterType {
+    type Error = PngError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(FilterType::None),
+            1 => Ok(FilterType::Sub),
+            2 => Ok(FilterType::Up),
+            3 => Ok(FilterType::Average),
+            4 => Ok(FilterType::Paeth),
+            _ => Err(PngError::InvalidImage(format!("Invalid filter type: {}", value))),
+        }
+    }
+}
+
+// Paeth 预测函数：在左、上、左上三个像素中选出最接近线性预测值的一个
+fn paeth_predictor(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// 原地还原一条被过滤的扫描行
+// bpp 为每个完整像素占用的字节数（不足 1 字节时取 1），prev 为已还原的上一行（首行传入全 0）
+pub fn unfilter(filter_type: FilterType, bpp: usize, prev: &[u8], current: &mut [u8]) {
+    match filter_type {
+        FilterType::None => {}
+        FilterType::Sub => {
+            for i in bpp..current.len() {
+                current[i] = current[i].wrapping_add(current[i - bpp]);
+            }
+        }
+        FilterType::Up => {
+            for i in 0..current.len() {
+                current[i] = current[i].wrapping_add(prev[i]);
+            }
+        }
+        FilterType::Average => {
+            for i in 0..current.len() {
+                let left = if i >= bpp { current[i - bpp] } else { 0 };
+                let average = ((left as u16 + prev[i] as u16) / 2) as u8;
+                current[i] = current[i].wrapping_add(average);
+            }
+        }
+        FilterType::Paeth => {
+            for i in 0..current.len() {
+                let (left, upper_left) = if i >= bpp { (current[i - bpp], prev[i - bpp]) } else { (0, 0) };
+                current[i] = current[i].wrapping_add(paeth_predictor(left, prev[i], upper_left));
+            }
+        }
+    }
+}
+
+// 对一条扫描行应用过滤，结果写入 out（不含过滤类型字节）
+pub fn filter(filter_type: FilterType, bpp: usize, prev: &[u8], current: &[u8], out: &mut [u8]) {
+    for i in 0..current.len() {
+        let left = if i >= bpp { current[i - bpp] } else { 0 };
+        let upper_left = if i >= bpp { prev[i - bpp] } else { 0 };
+        let predicted = match filter_type {
+            FilterType::None => 0,
+            FilterType::Sub => left,
+            FilterType::Up => prev[i],
+            FilterType::Average => ((left as u16 + prev[i] as u16) / 2) as u8,
+            FilterType::Paeth => paeth_predictor(left, prev[i], upper_left),
+        };
+        out[i] = current[i].wrapping_sub(predicted);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_type_from_u8() {
+        for filter_type in FilterType::ALL {
+            assert_eq!(FilterType::try_from(filter_type.value()).unwrap(), filter_type);
+        }
+        assert!(FilterType::try_from(5).is_err());
+    }
+
+    #[test]
+    fn test_paeth_predictor() {
+        assert_eq!(paeth_predictor(10, 20, 10), 20);
+        assert_eq!(paeth_predictor(20, 10, 10), 20);
+        assert_eq!(paeth_predictor(10, 10, 20), 10);
+    }
+
+    #[test]
+    fn test_unfilter_sub() {
+        let mut row = vec![1, 2, 3, 4, 5, 6];
+        unfilter(FilterType::Sub, 2, &[0; 6], &mut row);
+        assert_eq!(row, vec![1, 2, 4, 6, 9, 12]);
+    }
+
+    #[test]
+    fn test_unfilter_up() {
+        let mut row = vec![1, 2, 255];
+        unfilter(FilterType::Up, 1, &[10, 20, 2], &mut row);
+        assert_eq!(row, vec![11, 22, 1]);
+    }
+
+    #[test]
+    fn test_unfilter_average() {
+        let mut row = vec![5, 5];
+        unfilter(FilterType::Average, 1, &[10, 20], &mut row);
+        // 第一个字节: 5 + 10/2 = 10；第二个字节: 5 + (10 + 20)/2 = 20
+        assert_eq!(row, vec![10, 20]);
+    }
+
+    #[test]
+    fn test_filter_round_trip() {
+        let prev = [3, 200, 17, 90, 4, 255];
+        let current = [100, 7, 250, 3, 66, 128];
+        for filter_type in FilterType::ALL {
+            let mut row = [0u8; 6];
+            filter(filter_type, 2, &prev, &current, &mut row);
+            unfilter(filter_type, 2, &prev, &mut row);
+            assert_eq!(row, current);
+        }
+    }
+
+    #[test]
+    fn test_unfilter_paeth() {
+        let mut row = vec![1, 1];
+        unfilter(FilterType::Paeth, 1, &[10, 20], &mut row);
+        // 第一个字节预测值为上方像素 10；第二个字节 a=11,b=20,c=10 预测值为 20
+        assert_eq!(row, vec![11, 21]);
+    }
+}
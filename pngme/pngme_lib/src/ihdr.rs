@@ -0,0 +1,291 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::chunk::Chunk;
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+
+// PNG 规范定义的颜色类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    Grayscale,
+    Rgb,
+    Indexed,
+    GrayscaleAlpha,
+    Rgba,
+}
+
+impl ColorType {
+    // 返回颜色类型在 IHDR 中的编码值
+    pub fn value(&self) -> u8 {
+        match self {
+            ColorType::Grayscale => 0,
+            ColorType::Rgb => 2,
+            ColorType::Indexed => 3,
+            ColorType::GrayscaleAlpha => 4,
+            ColorType::Rgba => 6,
+        }
+    }
+
+    // 返回每个像素包含的通道数
+    pub fn channels(&self) -> usize {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+
+    // 检查该颜色类型是否允许使用给定的位深度
+    pub fn allows_bit_depth(&self, bit_depth: u8) -> bool {
+        match self {
+            ColorType::Grayscale => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+            ColorType::Indexed => matches!(bit_depth, 1 | 2 | 4 | 8),
+            ColorType::Rgb | ColorType::GrayscaleAlpha | ColorType::Rgba => matches!(bit_depth, 8 | 16),
+        }
+    }
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = PngError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(ColorType::Grayscale),
+            2 => Ok(ColorType::Rgb),
+            3 => Ok(ColorType::Indexed),
+            4 => Ok(ColorType::GrayscaleAlpha),
+            6 => Ok(ColorType::Rgba),
+            _ => Err(PngError::InvalidHeader(format!("Invalid color type: {}", value))),
+        }
+    }
+}
+
+impl fmt::Display for ColorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColorType::Grayscale => "Grayscale",
+            ColorType::Rgb => "RGB",
+            ColorType::Indexed => "Indexed",
+            ColorType::GrayscaleAlpha => "Grayscale+Alpha",
+            ColorType::Rgba => "RGBA",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// PNG 规范定义的隔行扫描方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterlaceMethod {
+    None,
+    Adam7,
+}
+
+impl TryFrom<u8> for InterlaceMethod {
+    type Error = PngError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(InterlaceMethod::None),
+            1 => Ok(InterlaceMethod::Adam7),
+            _ => Err(PngError::InvalidHeader(format!("Invalid interlace method: {}", value))),
+        }
+    }
+}
+
+impl fmt::Display for InterlaceMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterlaceMethod::None => write!(f, "none"),
+            InterlaceMethod::Adam7 => write!(f, "Adam7"),
+        }
+    }
+}
+
+// IHDR 数据块：描述图像的尺寸和像素格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ihdr {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: ColorType,
+    pub interlace_method: InterlaceMethod,
+}
+
+impl Ihdr {
+    // IHDR 数据块的数据长度固定为 13 字节
+    pub const LENGTH: usize = 13;
+
+    // 创建并校验新的 IHDR
+    pub fn new(width: u32, height: u32, bit_depth: u8, color_type: ColorType, interlace_method: InterlaceMethod) -> Result<Ihdr> {
+        let ihdr = Ihdr {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            interlace_method,
+        };
+        ihdr.validate()?;
+        Ok(ihdr)
+    }
+
+    // 返回 IHDR 的数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("IHDR").unwrap()
+    }
+
+    // 每个像素占用的位数
+    pub fn bits_per_pixel(&self) -> usize {
+        self.color_type.channels() * self.bit_depth as usize
+    }
+
+    // 将 IHDR 编码为数据块
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(Self::LENGTH);
+        data.extend_from_slice(&self.width.to_be_bytes());
+        data.extend_from_slice(&self.height.to_be_bytes());
+        data.push(self.bit_depth);
+        data.push(self.color_type.value());
+        // 压缩方法和过滤方法只定义了 0
+        data.push(0);
+        data.push(0);
+        data.push(match self.interlace_method {
+            InterlaceMethod::None => 0,
+            InterlaceMethod::Adam7 => 1,
+        });
+        Chunk::new(Self::chunk_type(), data)
+    }
+
+    // 检查尺寸以及颜色类型与位深度的组合是否符合规范
+    fn validate(&self) -> Result<()> {
+        if self.width == 0 || self.width > i32::MAX as u32 {
+            return Err(PngError::InvalidHeader(format!("Invalid image width: {}", self.width)));
+        }
+        if self.height == 0 || self.height > i32::MAX as u32 {
+            return Err(PngError::InvalidHeader(format!("Invalid image height: {}", self.height)));
+        }
+        if !self.color_type.allows_bit_depth(self.bit_depth) {
+            return Err(PngError::InvalidHeader(format!(
+                "Invalid bit depth {} for color type {}",
+                self.bit_depth, self.color_type
+            )));
+        }
+        Ok(())
+    }
+}
+
+// 从 IHDR 数据块的数据部分解析
+impl TryFrom<&[u8]> for Ihdr {
+    type Error = PngError;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        if value.len() != Self::LENGTH {
+            return Err(PngError::InvalidHeader(format!("Invalid IHDR length: {}", value.len())));
+        }
+        let width = u32::from_be_bytes([value[0], value[1], value[2], value[3]]);
+        let height = u32::from_be_bytes([value[4], value[5], value[6], value[7]]);
+        let bit_depth = value[8];
+        let color_type = ColorType::try_from(value[9])?;
+        if value[10] != 0 {
+            return Err(PngError::InvalidHeader(format!("Invalid compression method: {}", value[10])));
+        }
+        if value[11] != 0 {
+            return Err(PngError::InvalidHeader(format!("Invalid filter method: {}", value[11])));
+        }
+        let interlace_method = InterlaceMethod::try_from(value[12])?;
+        Ihdr::new(width, height, bit_depth, color_type, interlace_method)
+    }
+}
+
+impl TryFrom<&ChunkRef<'_>> for Ihdr {
+    type Error = PngError;
+
+    fn try_from(chunk: &ChunkRef<'_>) -> Result<Self> {
+        if *chunk.chunk_type() != Self::chunk_type() {
+            return Err(PngError::InvalidHeader(format!("Expected IHDR chunk, found {}", chunk.chunk_type())));
+        }
+        Ihdr::try_from(chunk.data())
+    }
+}
+
+impl TryFrom<&Chunk> for Ihdr {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        Ihdr::try_from(&ChunkRef::from(chunk))
+    }
+}
+
+impl fmt::Display for Ihdr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}, bit depth: {}, color type: {}, interlace: {}",
+            self.width, self.height, self.bit_depth, self.color_type, self.interlace_method
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ihdr_bytes(bit_depth: u8, color_type: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&1502u32.to_be_bytes());
+        bytes.extend_from_slice(&1125u32.to_be_bytes());
+        bytes.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn test_ihdr_from_bytes() {
+        let ihdr = Ihdr::try_from(ihdr_bytes(8, 6).as_slice()).unwrap();
+        assert_eq!(ihdr.width, 1502);
+        assert_eq!(ihdr.height, 1125);
+        assert_eq!(ihdr.bit_depth, 8);
+        assert_eq!(ihdr.color_type, ColorType::Rgba);
+        assert_eq!(ihdr.interlace_method, InterlaceMethod::None);
+        assert_eq!(ihdr.bits_per_pixel(), 32);
+    }
+
+    #[test]
+    fn test_ihdr_invalid_combinations() {
+        assert!(Ihdr::try_from(ihdr_bytes(16, 3).as_slice()).is_err());
+        assert!(Ihdr::try_from(ihdr_bytes(4, 2).as_slice()).is_err());
+        assert!(Ihdr::try_from(ihdr_bytes(8, 5).as_slice()).is_err());
+        assert!(Ihdr::try_from(ihdr_bytes(1, 0).as_slice()).is_ok());
+    }
+
+    #[test]
+    fn test_ihdr_invalid_methods() {
+        let mut bytes = ihdr_bytes(8, 2);
+        bytes[10] = 1;
+        assert!(Ihdr::try_from(bytes.as_slice()).is_err());
+        let mut bytes = ihdr_bytes(8, 2);
+        bytes[11] = 1;
+        assert!(Ihdr::try_from(bytes.as_slice()).is_err());
+        let mut bytes = ihdr_bytes(8, 2);
+        bytes[12] = 2;
+        assert!(Ihdr::try_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_ihdr_zero_dimensions() {
+        let mut bytes = ihdr_bytes(8, 2);
+        bytes[0..4].copy_from_slice(&0u32.to_be_bytes());
+        assert!(Ihdr::try_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_ihdr_chunk_round_trip() {
+        let ihdr = Ihdr::new(16, 9, 4, ColorType::Indexed, InterlaceMethod::Adam7).unwrap();
+        let chunk = ihdr.to_chunk();
+        assert_eq!(chunk.length() as usize, Ihdr::LENGTH);
+        assert_eq!(Ihdr::try_from(&chunk).unwrap(), ihdr);
+    }
+}
@@ -0,0 +1,584 @@
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::str::FromStr;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+use crate::filter::{self, FilterType};
+use crate::ihdr::{ColorType, Ihdr, InterlaceMethod};
+use crate::interlace;
+
+// 单个 IDAT 数据块的最大数据长度
+const MAX_IDAT_LENGTH: usize = 8192;
+
+// 解码后的图像
+// pixels 按行存放去除过滤字节后的原始样本，每行 row_bytes() 字节；
+// 位深度小于 8 时多个像素打包在同一字节中，16 位样本按大端序存放
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub color_type: ColorType,
+    pub bit_depth: u8,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    // 每个像素占用的位数
+    pub fn bits_per_pixel(&self) -> usize {
+        self.color_type.channels() * self.bit_depth as usize
+    }
+
+    // 每行像素占用的字节数
+    pub fn row_bytes(&self) -> usize {
+        row_bytes(self.width, self.bits_per_pixel())
+    }
+
+    // 返回第 y 行的像素数据
+    pub fn row(&self, y: u32) -> &[u8] {
+        let row_bytes = self.row_bytes();
+        let start = y as usize * row_bytes;
+        &self.pixels[start..start + row_bytes]
+    }
+
+    // 返回 (x, y) 处像素的第 channel 个样本，位深度小于 8 时从打包的字节中取出
+    fn sample(&self, x: usize, y: u32, channel: usize) -> u16 {
+        let row = self.row(y);
+        let index = x * self.color_type.channels() + channel;
+        match self.bit_depth {
+            16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+            8 => row[index] as u16,
+            depth => {
+                let bit = index * depth as usize;
+                let shift = 8 - depth as usize - bit % 8;
+                ((row[bit / 8] >> shift) & ((1u8 << depth) - 1)) as u16
+            }
+        }
+    }
+
+    // 检查像素缓冲区的长度是否等于 height 行、每行 row_bytes() 字节
+    fn check_pixels(&self) -> Result<()> {
+        let expected = (self.height as usize)
+            .checked_mul(self.row_bytes())
+            .ok_or_else(|| PngError::InvalidImage(format!("Image of {}x{} is too large", self.width, self.height)))?;
+        if self.pixels.len() != expected {
+            return Err(PngError::InvalidImage(format!(
+                "Pixel buffer has {} bytes, expected {}",
+                self.pixels.len(),
+                expected
+            )));
+        }
+        Ok(())
+    }
+
+    // 把像素展开为每像素 4 字节的 8 位 RGBA，16 位样本按比例缩放到 8 位
+    // palette 为 PLTE 数据，索引色图像必须提供；transparency 为可选的 tRNS 数据
+    // 字段由调用者构造，先按 Ihdr::new 的规则检查尺寸、颜色类型和位深度，再检查像素缓冲区长度
+    pub fn to_rgba8(&self, palette: Option<&[u8]>, transparency: Option<&[u8]>) -> Result<Vec<u8>> {
+        Ihdr::new(self.width, self.height, self.bit_depth, self.color_type, InterlaceMethod::None)
+            .map_err(|e| PngError::InvalidImage(e.to_string()))?;
+        self.check_pixels()?;
+        let max = (1u32 << self.bit_depth) - 1;
+        let scale = |value: u16| (value as u32 * 255 / max) as u8;
+        // 灰度和 RGB 图像的 tRNS 指定一个完全透明的颜色，每个样本占 2 字节
+        let key: Option<Vec<u16>> = match (self.color_type, transparency) {
+            (ColorType::Grayscale | ColorType::Rgb, Some(trns)) => {
+                Some(trns.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect())
+            }
+            _ => None,
+        };
+        let mut rgba = Vec::with_capacity(self.width as usize * self.height as usize * 4);
+        for y in 0..self.height {
+            for x in 0..self.width as usize {
+                let sample = |channel| self.sample(x, y, channel);
+                let opaque = |samples: &[u16]| if key.as_deref() == Some(samples) { 0 } else { 255 };
+                match self.color_type {
+                    ColorType::Grayscale => {
+                        let gray = sample(0);
+                        rgba.extend_from_slice(&[scale(gray), scale(gray), scale(gray), opaque(&[gray])]);
+                    }
+                    ColorType::Rgb => {
+                        let (r, g, b) = (sample(0), sample(1), sample(2));
+                        rgba.extend_from_slice(&[scale(r), scale(g), scale(b), opaque(&[r, g, b])]);
+                    }
+                    ColorType::Indexed => {
+                        let index = sample(0) as usize;
+                        let color = palette
+                            .and_then(|p| p.get(index * 3..index * 3 + 3))
+                            .ok_or_else(|| PngError::InvalidImage(format!("Palette index {} out of range", index)))?;
+                        let alpha = transparency.and_then(|t| t.get(index)).copied().unwrap_or(255);
+                        rgba.extend_from_slice(&[color[0], color[1], color[2], alpha]);
+                    }
+                    ColorType::GrayscaleAlpha => {
+                        let gray = scale(sample(0));
+                        rgba.extend_from_slice(&[gray, gray, gray, scale(sample(1))]);
+                    }
+                    ColorType::Rgba => {
+                        rgba.extend_from_slice(&[scale(sample(0)), scale(sample(1)), scale(sample(2)), scale(sample(3))]);
+                    }
+                }
+            }
+        }
+        Ok(rgba)
+    }
+}
+
+// 编码时为每条扫描行选择过滤类型的策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterStrategy {
+    // 所有扫描行都使用同一种过滤类型
+    Fixed(FilterType),
+    // 选择过滤结果（按有符号字节计）绝对值之和最小的过滤类型
+    MinimumSum,
+    // 逐一压缩每种过滤结果，选择压缩后最短的过滤类型
+    BruteForce,
+}
+
+// 编码图像时的选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeOptions {
+    pub filter_strategy: FilterStrategy,
+    // zlib 压缩级别，取值 0-9
+    pub compression_level: u32,
+    // 调色板，按 RGB 三元组依次存放；索引色图像必须提供
+    pub palette: Option<Vec<u8>>,
+    pub interlace_method: InterlaceMethod,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        EncodeOptions {
+            filter_strategy: FilterStrategy::MinimumSum,
+            compression_level: 6,
+            palette: None,
+            interlace_method: InterlaceMethod::None,
+        }
+    }
+}
+
+// 计算给定宽度和像素位数时一行占用的字节数
+pub(crate) fn row_bytes(width: u32, bits_per_pixel: usize) -> usize {
+    (width as usize * bits_per_pixel).div_ceil(8)
+}
+
+// 过滤算法中“前一个像素”的字节距离，不足 1 字节的像素按 1 字节计算
+pub(crate) fn filter_bpp(bits_per_pixel: usize) -> usize {
+    bits_per_pixel.div_ceil(8)
+}
+
+// 计算 height 行扫描行的总字节数，每行由 1 字节过滤类型加 row_bytes 字节数据组成
+// IHDR 中的宽高来自文件，乘积溢出时报错而不是回绕
+pub(crate) fn scanlines_size(height: usize, row_bytes: usize) -> Result<usize> {
+    row_bytes
+        .checked_add(1)
+        .and_then(|line| line.checked_mul(height))
+        .ok_or_else(|| PngError::InvalidImage(format!("Image of {} rows with {} bytes per row is too large", height, row_bytes)))
+}
+
+// 解压 zlib 数据流，最多接受 limit 字节的解压结果，防止很小的 IDAT 解压出耗尽内存的数据
+pub(crate) fn inflate(compressed: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut decoder = ZlibDecoder::new(compressed).take((limit as u64).saturating_add(1));
+    let mut data = Vec::new();
+    decoder
+        .read_to_end(&mut data)
+        .map_err(|e| PngError::Compression(format!("Failed to inflate image data: {}", e)))?;
+    if data.len() > limit {
+        return Err(PngError::Compression(format!("Image data exceeds the expected {} bytes", limit)));
+    }
+    Ok(data)
+}
+
+// 还原 height 行被过滤的扫描行，每行由 1 字节过滤类型加 row_bytes 字节数据组成
+pub(crate) fn unfilter_scanlines(data: &[u8], height: usize, row_bytes: usize, bpp: usize) -> Result<Vec<u8>> {
+    let expected = scanlines_size(height, row_bytes)?;
+    if data.len() < expected {
+        return Err(PngError::InvalidImage(format!(
+            "Image data too short: expected {} bytes, found {}",
+            expected,
+            data.len()
+        )));
+    }
+    // height * row_bytes 小于已检查过的 expected，不会溢出；数据足够长之后才按 IHDR 的尺寸分配内存
+    let mut pixels = vec![0u8; height * row_bytes];
+    let zero_row = vec![0u8; row_bytes];
+    for y in 0..height {
+        let line = &data[y * (row_bytes + 1)..(y + 1) * (row_bytes + 1)];
+        let filter_type = FilterType::try_from(line[0])?;
+        let (done, rest) = pixels.split_at_mut(y * row_bytes);
+        let prev = if y == 0 { &zero_row[..] } else { &done[(y - 1) * row_bytes..] };
+        let current = &mut rest[..row_bytes];
+        current.copy_from_slice(&line[1..]);
+        filter::unfilter(filter_type, bpp, prev, current);
+    }
+    Ok(pixels)
+}
+
+// 将解压后的 IDAT 数据还原为图像
+pub(crate) fn decode(ihdr: &Ihdr, compressed: &[u8]) -> Result<Image> {
+    if ihdr.interlace_method == InterlaceMethod::Adam7 {
+        return interlace::decode(ihdr, compressed);
+    }
+    let bits_per_pixel = ihdr.bits_per_pixel();
+    let row_bytes = row_bytes(ihdr.width, bits_per_pixel);
+    let data = inflate(compressed, scanlines_size(ihdr.height as usize, row_bytes)?)?;
+    let pixels = unfilter_scanlines(&data, ihdr.height as usize, row_bytes, filter_bpp(bits_per_pixel))?;
+    Ok(Image {
+        width: ihdr.width,
+        height: ihdr.height,
+        color_type: ihdr.color_type,
+        bit_depth: ihdr.bit_depth,
+        pixels,
+    })
+}
+
+// 压缩数据为 zlib 数据流
+pub(crate) fn deflate(data: &[u8], level: u32) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+// 按策略为一条扫描行选择过滤类型，并把过滤类型字节和过滤结果追加到 out
+fn filter_scanline(strategy: FilterStrategy, level: u32, bpp: usize, prev: &[u8], current: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    let mut candidate = vec![0u8; current.len()];
+    let filter_type = match strategy {
+        FilterStrategy::Fixed(filter_type) => filter_type,
+        FilterStrategy::MinimumSum => {
+            let mut best = (FilterType::None, u64::MAX);
+            for filter_type in FilterType::ALL {
+                filter::filter(filter_type, bpp, prev, current, &mut candidate);
+                let sum = candidate.iter().map(|&b| (b as i8).unsigned_abs() as u64).sum();
+                if sum < best.1 {
+                    best = (filter_type, sum);
+                }
+            }
+            best.0
+        }
+        FilterStrategy::BruteForce => {
+            let mut best = (FilterType::None, usize::MAX);
+            for filter_type in FilterType::ALL {
+                filter::filter(filter_type, bpp, prev, current, &mut candidate);
+                let size = deflate(&candidate, level)?.len();
+                if size < best.1 {
+                    best = (filter_type, size);
+                }
+            }
+            best.0
+        }
+    };
+    filter::filter(filter_type, bpp, prev, current, &mut candidate);
+    out.push(filter_type.value());
+    out.extend_from_slice(&candidate);
+    Ok(())
+}
+
+// 对 height 行像素数据逐行过滤，返回带过滤类型字节的扫描行序列
+pub(crate) fn filter_scanlines(pixels: &[u8], height: usize, row_bytes: usize, bpp: usize, options: &EncodeOptions) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(height * (row_bytes + 1));
+    let zero_row = vec![0u8; row_bytes];
+    for y in 0..height {
+        let prev = if y == 0 { &zero_row[..] } else { &pixels[(y - 1) * row_bytes..y * row_bytes] };
+        let current = &pixels[y * row_bytes..(y + 1) * row_bytes];
+        filter_scanline(options.filter_strategy, options.compression_level, bpp, prev, current, &mut data)?;
+    }
+    Ok(data)
+}
+
+// 校验调色板是否与颜色类型和位深度相符，并返回 PLTE 数据块
+fn palette_chunk(ihdr: &Ihdr, palette: Option<&Vec<u8>>) -> Result<Option<Chunk>> {
+    let palette = match (ihdr.color_type, palette) {
+        (ColorType::Indexed, None) => return Err(PngError::InvalidImage("Indexed images require a palette".to_string())),
+        (ColorType::Grayscale | ColorType::GrayscaleAlpha, Some(_)) => {
+            return Err(PngError::InvalidImage(format!("Palette is not allowed for color type {}", ihdr.color_type)))
+        }
+        (_, None) => return Ok(None),
+        (_, Some(palette)) => palette,
+    };
+    if palette.is_empty() || palette.len() % 3 != 0 || palette.len() / 3 > 256 {
+        return Err(PngError::InvalidImage(format!("Invalid palette length: {}", palette.len())));
+    }
+    if ihdr.color_type == ColorType::Indexed && palette.len() / 3 > 1 << ihdr.bit_depth {
+        return Err(PngError::InvalidImage(format!(
+            "Palette has {} entries but bit depth {} allows at most {}",
+            palette.len() / 3,
+            ihdr.bit_depth,
+            1 << ihdr.bit_depth
+        )));
+    }
+    Ok(Some(Chunk::new(ChunkType::from_str("PLTE")?, palette.clone())))
+}
+
+// 把图像编码为 IHDR、PLTE、IDAT、IEND 数据块序列
+pub(crate) fn encode(image: &Image, options: &EncodeOptions) -> Result<Vec<Chunk>> {
+    let ihdr = Ihdr::new(image.width, image.height, image.bit_depth, image.color_type, options.interlace_method)?;
+    let row_bytes = image.row_bytes();
+    image.check_pixels()?;
+    if options.compression_level > 9 {
+        return Err(PngError::InvalidImage(format!("Invalid compression level: {}", options.compression_level)));
+    }
+
+    let mut chunks = vec![ihdr.to_chunk()];
+    if let Some(plte) = palette_chunk(&ihdr, options.palette.as_ref())? {
+        chunks.push(plte);
+    }
+    let filtered = match options.interlace_method {
+        InterlaceMethod::None => filter_scanlines(&image.pixels, image.height as usize, row_bytes, filter_bpp(image.bits_per_pixel()), options)?,
+        InterlaceMethod::Adam7 => interlace::filter_passes(image, options)?,
+    };
+    let compressed = deflate(&filtered, options.compression_level)?;
+    let idat = ChunkType::from_str("IDAT")?;
+    for piece in compressed.chunks(MAX_IDAT_LENGTH) {
+        chunks.push(Chunk::new(idat, piece.to_vec()));
+    }
+    chunks.push(Chunk::new(ChunkType::from_str("IEND")?, Vec::new()));
+    Ok(chunks)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        super::deflate(data, 6).unwrap()
+    }
+
+    fn gradient_image() -> Image {
+        let mut pixels = Vec::new();
+        for y in 0..16u8 {
+            for x in 0..16u8 {
+                pixels.extend_from_slice(&[x * 16, y * 16, x ^ y]);
+            }
+        }
+        Image {
+            width: 16,
+            height: 16,
+            color_type: ColorType::Rgb,
+            bit_depth: 8,
+            pixels,
+        }
+    }
+
+    fn decode_chunks(chunks: &[Chunk]) -> Image {
+        let ihdr = Ihdr::try_from(&chunks[0]).unwrap();
+        let compressed: Vec<u8> = chunks
+            .iter()
+            .filter(|c| c.chunk_type().to_string() == "IDAT")
+            .flat_map(|c| c.data().to_vec())
+            .collect();
+        decode(&ihdr, &compressed).unwrap()
+    }
+
+    #[test]
+    fn test_to_rgba8() {
+        let gray = Image {
+            width: 4,
+            height: 1,
+            color_type: ColorType::Grayscale,
+            bit_depth: 2,
+            pixels: vec![0b00_01_10_11],
+        };
+        let rgba = gray.to_rgba8(None, Some(&[0, 2])).unwrap();
+        assert_eq!(rgba, [0, 0, 0, 255, 85, 85, 85, 255, 170, 170, 170, 0, 255, 255, 255, 255]);
+
+        let indexed = Image {
+            width: 2,
+            height: 1,
+            color_type: ColorType::Indexed,
+            bit_depth: 8,
+            pixels: vec![1, 0],
+        };
+        let palette = [10, 20, 30, 40, 50, 60];
+        assert_eq!(indexed.to_rgba8(Some(&palette), Some(&[128])).unwrap(), [40, 50, 60, 255, 10, 20, 30, 128]);
+        assert!(indexed.to_rgba8(Some(&palette[..3]), None).is_err());
+
+        let rgb16 = Image {
+            width: 1,
+            height: 1,
+            color_type: ColorType::Rgb,
+            bit_depth: 16,
+            pixels: vec![0xFF, 0xFF, 0x80, 0x00, 0, 0],
+        };
+        assert_eq!(rgb16.to_rgba8(None, None).unwrap(), [255, 127, 0, 255]);
+    }
+
+    #[test]
+    fn test_to_rgba8_rejects_invalid_fields() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            color_type: ColorType::Grayscale,
+            bit_depth: 8,
+            pixels: vec![0, 255],
+        };
+        assert!(image.to_rgba8(None, None).is_ok());
+        for bit_depth in [0, 3, 32, 255] {
+            let invalid = Image { bit_depth, ..image.clone() };
+            assert!(matches!(invalid.to_rgba8(None, None), Err(PngError::InvalidImage(_))));
+        }
+        let short = Image { pixels: vec![0], ..image.clone() };
+        assert!(matches!(short.to_rgba8(None, None), Err(PngError::InvalidImage(_))));
+        let huge = Image { width: i32::MAX as u32, height: i32::MAX as u32, ..image };
+        assert!(matches!(huge.to_rgba8(None, None), Err(PngError::InvalidImage(_))));
+    }
+
+    #[test]
+    fn test_row_bytes() {
+        assert_eq!(row_bytes(3, 1), 1);
+        assert_eq!(row_bytes(9, 1), 2);
+        assert_eq!(row_bytes(3, 24), 9);
+        assert_eq!(filter_bpp(2), 1);
+        assert_eq!(filter_bpp(48), 6);
+    }
+
+    #[test]
+    fn test_decode_filtered_rgb() {
+        let ihdr = Ihdr::new(2, 2, 8, ColorType::Rgb, InterlaceMethod::None).unwrap();
+        // 第一行使用 Sub 过滤，第二行使用 Up 过滤
+        let filtered = [
+            1, 10, 20, 30, 5, 5, 5,
+            2, 1, 1, 1, 1, 1, 1,
+        ];
+        let image = decode(&ihdr, &deflate(&filtered)).unwrap();
+        assert_eq!(image.row(0), &[10, 20, 30, 15, 25, 35]);
+        assert_eq!(image.row(1), &[11, 21, 31, 16, 26, 36]);
+    }
+
+    #[test]
+    fn test_decode_packed_grayscale() {
+        let ihdr = Ihdr::new(10, 1, 1, ColorType::Grayscale, InterlaceMethod::None).unwrap();
+        let filtered = [0, 0b1010_1010, 0b1100_0000];
+        let image = decode(&ihdr, &deflate(&filtered)).unwrap();
+        assert_eq!(image.row_bytes(), 2);
+        assert_eq!(image.pixels, vec![0b1010_1010, 0b1100_0000]);
+    }
+
+    #[test]
+    fn test_decode_short_data() {
+        let ihdr = Ihdr::new(2, 2, 8, ColorType::Rgb, InterlaceMethod::None).unwrap();
+        assert!(decode(&ihdr, &deflate(&[0, 1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn test_decode_oversized_header() {
+        let max = i32::MAX as u32;
+        for interlace_method in [InterlaceMethod::None, InterlaceMethod::Adam7] {
+            let ihdr = Ihdr::new(max, max, 16, ColorType::Rgba, interlace_method).unwrap();
+            assert!(matches!(decode(&ihdr, &deflate(&[0; 16])), Err(PngError::InvalidImage(_))));
+            let ihdr = Ihdr::new(max, 4, 8, ColorType::Grayscale, interlace_method).unwrap();
+            assert!(decode(&ihdr, &deflate(&[0; 16])).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_excess_data() {
+        // 1x1 灰度图像只需要 2 字节，过长的解压结果在读到上限后即报错
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Grayscale, InterlaceMethod::None).unwrap();
+        let bomb = deflate(&vec![0; 1 << 20]);
+        assert!(matches!(decode(&ihdr, &bomb), Err(PngError::Compression(_))));
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Grayscale, InterlaceMethod::Adam7).unwrap();
+        assert!(matches!(decode(&ihdr, &bomb), Err(PngError::Compression(_))));
+    }
+
+    #[test]
+    fn test_decode_invalid_filter() {
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Grayscale, InterlaceMethod::None).unwrap();
+        assert!(decode(&ihdr, &deflate(&[9, 0])).is_err());
+    }
+
+    #[test]
+    fn test_decode_corrupt_stream() {
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Grayscale, InterlaceMethod::None).unwrap();
+        assert!(decode(&ihdr, &[1, 2, 3, 4]).is_err());
+    }
+
+    #[test]
+    fn test_encode_round_trip_all_strategies() {
+        let image = gradient_image();
+        let strategies = [
+            FilterStrategy::Fixed(FilterType::Paeth),
+            FilterStrategy::MinimumSum,
+            FilterStrategy::BruteForce,
+        ];
+        for strategy in strategies {
+            let options = EncodeOptions {
+                filter_strategy: strategy,
+                ..EncodeOptions::default()
+            };
+            let chunks = encode(&image, &options).unwrap();
+            assert_eq!(chunks.first().unwrap().chunk_type().to_string(), "IHDR");
+            assert_eq!(chunks.last().unwrap().chunk_type().to_string(), "IEND");
+            assert_eq!(decode_chunks(&chunks), image);
+        }
+    }
+
+    #[test]
+    fn test_encode_fixed_filter_is_used() {
+        let image = gradient_image();
+        let options = EncodeOptions {
+            filter_strategy: FilterStrategy::Fixed(FilterType::Up),
+            ..EncodeOptions::default()
+        };
+        let filtered = filter_scanlines(&image.pixels, 16, image.row_bytes(), 3, &options).unwrap();
+        assert!(filtered.chunks(image.row_bytes() + 1).all(|line| line[0] == FilterType::Up.value()));
+    }
+
+    #[test]
+    fn test_encode_indexed_requires_palette() {
+        let image = Image {
+            width: 4,
+            height: 1,
+            color_type: ColorType::Indexed,
+            bit_depth: 2,
+            pixels: vec![0b00_01_10_11],
+        };
+        assert!(encode(&image, &EncodeOptions::default()).is_err());
+        let options = EncodeOptions {
+            palette: Some(vec![0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255]),
+            ..EncodeOptions::default()
+        };
+        let chunks = encode(&image, &options).unwrap();
+        assert_eq!(chunks[1].chunk_type().to_string(), "PLTE");
+        assert_eq!(decode_chunks(&chunks), image);
+    }
+
+    #[test]
+    fn test_encode_palette_too_large() {
+        let image = Image {
+            width: 1,
+            height: 1,
+            color_type: ColorType::Indexed,
+            bit_depth: 1,
+            pixels: vec![0],
+        };
+        let options = EncodeOptions {
+            palette: Some(vec![0; 9]),
+            ..EncodeOptions::default()
+        };
+        assert!(encode(&image, &options).is_err());
+    }
+
+    #[test]
+    fn test_encode_wrong_pixel_length() {
+        let mut image = gradient_image();
+        image.pixels.pop();
+        assert!(encode(&image, &EncodeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_encode_interlaced_round_trip() {
+        let image = gradient_image();
+        let options = EncodeOptions {
+            interlace_method: InterlaceMethod::Adam7,
+            ..EncodeOptions::default()
+        };
+        let chunks = encode(&image, &options).unwrap();
+        let ihdr = Ihdr::try_from(&chunks[0]).unwrap();
+        assert_eq!(ihdr.interlace_method, InterlaceMethod::Adam7);
+        assert_eq!(decode_chunks(&chunks), image);
+    }
+}
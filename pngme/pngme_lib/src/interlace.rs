@@ -0,0 +1,348 @@
+use crate::error::{PngError, Result};
+use crate::ihdr::{Ihdr, InterlaceMethod};
+use crate::image::{self, EncodeOptions, Image};
+
+// Adam7 七个扫描遍的起始列、起始行、列步长、行步长
+const ADAM7: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+// 一个扫描遍的图像
+// index 为规范中的遍号 1-7；非隔行图像只有一遍，遍号为 0，覆盖整幅图像
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pass {
+    pub index: usize,
+    pub x_start: u32,
+    pub y_start: u32,
+    pub x_step: u32,
+    pub y_step: u32,
+    pub image: Image,
+}
+
+impl Pass {
+    // 把本遍的像素写回完整尺寸的图像中对应的位置
+    pub fn scatter_into(&self, target: &mut Image) {
+        let bits = self.image.bits_per_pixel();
+        let target_row_bytes = target.row_bytes();
+        for y in 0..self.image.height {
+            let source = self.image.row(y);
+            let target_y = (self.y_start + y * self.y_step) as usize;
+            let target_row = &mut target.pixels[target_y * target_row_bytes..(target_y + 1) * target_row_bytes];
+            for x in 0..self.image.width {
+                let target_x = (self.x_start + x * self.x_step) as usize;
+                copy_pixel(source, x as usize, target_row, target_x, bits);
+            }
+        }
+    }
+}
+
+// 计算某一遍的宽和高，可能为 0
+fn pass_size(pass: usize, width: u32, height: u32) -> (u32, u32) {
+    let (x_start, y_start, x_step, y_step) = ADAM7[pass];
+    let w = if width > x_start { (width - x_start).div_ceil(x_step) } else { 0 };
+    let h = if height > y_start { (height - y_start).div_ceil(y_step) } else { 0 };
+    (w, h)
+}
+
+// 解压后的 IDAT 数据应有的字节数：非隔行图像为整幅图像的扫描行，Adam7 为各个非空遍的扫描行之和
+pub(crate) fn data_size(ihdr: &Ihdr) -> Result<usize> {
+    let bits_per_pixel = ihdr.bits_per_pixel();
+    if ihdr.interlace_method == InterlaceMethod::None {
+        return image::scanlines_size(ihdr.height as usize, image::row_bytes(ihdr.width, bits_per_pixel));
+    }
+    let mut total: usize = 0;
+    for pass in 0..ADAM7.len() {
+        let (width, height) = pass_size(pass, ihdr.width, ihdr.height);
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let size = image::scanlines_size(height as usize, image::row_bytes(width, bits_per_pixel))?;
+        total = total
+            .checked_add(size)
+            .ok_or_else(|| PngError::InvalidImage("Interlaced image is too large".to_string()))?;
+    }
+    Ok(total)
+}
+
+// 按位复制一个像素，兼容位深度小于 8 的打包格式
+fn copy_pixel(source: &[u8], source_x: usize, target: &mut [u8], target_x: usize, bits: usize) {
+    if bits >= 8 {
+        let bytes = bits / 8;
+        target[target_x * bytes..(target_x + 1) * bytes].copy_from_slice(&source[source_x * bytes..(source_x + 1) * bytes]);
+        return;
+    }
+    let mask = (1u8 << bits) - 1;
+    let source_bit = source_x * bits;
+    let value = (source[source_bit / 8] >> (8 - bits - source_bit % 8)) & mask;
+    let target_bit = target_x * bits;
+    let shift = 8 - bits - target_bit % 8;
+    let byte = &mut target[target_bit / 8];
+    *byte = (*byte & !(mask << shift)) | (value << shift);
+}
+
+// 逐遍解码 Adam7 隔行图像的迭代器，可用于先渲染低分辨率预览
+pub struct Passes {
+    ihdr: Ihdr,
+    data: Vec<u8>,
+    offset: usize,
+    next_pass: usize,
+    finished: bool,
+}
+
+impl Passes {
+    // 解压 IDAT 数据，准备逐遍还原
+    // 先确认数据完整，blank_image 和各遍才能安全地按 IHDR 的尺寸分配
+    pub(crate) fn new(ihdr: Ihdr, compressed: &[u8]) -> Result<Passes> {
+        let expected = data_size(&ihdr)?;
+        let data = image::inflate(compressed, expected)?;
+        if data.len() < expected {
+            return Err(PngError::InvalidImage(format!(
+                "Image data too short: expected {} bytes, found {}",
+                expected,
+                data.len()
+            )));
+        }
+        Ok(Passes {
+            ihdr,
+            data,
+            offset: 0,
+            next_pass: 0,
+            finished: false,
+        })
+    }
+
+    // 返回完整尺寸、像素全部为 0 的空白图像，用于累积各遍结果
+    pub fn blank_image(&self) -> Image {
+        blank_image(&self.ihdr)
+    }
+
+    // 还原一幅 width x height 的子图像并推进读取位置
+    fn read_image(&mut self, width: u32, height: u32) -> Result<Image> {
+        let bits_per_pixel = self.ihdr.bits_per_pixel();
+        let row_bytes = image::row_bytes(width, bits_per_pixel);
+        let length = image::scanlines_size(height as usize, row_bytes)?;
+        let pixels = image::unfilter_scanlines(
+            self.data.get(self.offset..).unwrap_or(&[]),
+            height as usize,
+            row_bytes,
+            image::filter_bpp(bits_per_pixel),
+        )?;
+        self.offset += length;
+        Ok(Image {
+            width,
+            height,
+            color_type: self.ihdr.color_type,
+            bit_depth: self.ihdr.bit_depth,
+            pixels,
+        })
+    }
+
+    fn read_pass(&mut self) -> Result<Option<Pass>> {
+        if self.ihdr.interlace_method == InterlaceMethod::None {
+            if self.next_pass > 0 {
+                return Ok(None);
+            }
+            self.next_pass = 1;
+            let image = self.read_image(self.ihdr.width, self.ihdr.height)?;
+            return Ok(Some(Pass {
+                index: 0,
+                x_start: 0,
+                y_start: 0,
+                x_step: 1,
+                y_step: 1,
+                image,
+            }));
+        }
+        // 跳过宽或高为 0 的空遍，它们在数据流中不占任何字节
+        while self.next_pass < ADAM7.len() {
+            let pass = self.next_pass;
+            self.next_pass += 1;
+            let (width, height) = pass_size(pass, self.ihdr.width, self.ihdr.height);
+            if width == 0 || height == 0 {
+                continue;
+            }
+            let image = self.read_image(width, height)?;
+            let (x_start, y_start, x_step, y_step) = ADAM7[pass];
+            return Ok(Some(Pass {
+                index: pass + 1,
+                x_start,
+                y_start,
+                x_step,
+                y_step,
+                image,
+            }));
+        }
+        Ok(None)
+    }
+}
+
+impl Iterator for Passes {
+    type Item = Result<Pass>;
+
+    // 出错后不再继续产出
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_pass() {
+            Ok(pass) => pass.map(Ok),
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn blank_image(ihdr: &Ihdr) -> Image {
+    Image {
+        width: ihdr.width,
+        height: ihdr.height,
+        color_type: ihdr.color_type,
+        bit_depth: ihdr.bit_depth,
+        pixels: vec![0u8; ihdr.height as usize * image::row_bytes(ihdr.width, ihdr.bits_per_pixel())],
+    }
+}
+
+// 解码 Adam7 隔行图像：逐遍还原后合成完整图像
+pub(crate) fn decode(ihdr: &Ihdr, compressed: &[u8]) -> Result<Image> {
+    let passes = Passes::new(*ihdr, compressed)?;
+    let mut target = blank_image(ihdr);
+    for pass in passes {
+        pass?.scatter_into(&mut target);
+    }
+    Ok(target)
+}
+
+// 从完整图像中抽取某一遍的子图像
+fn gather_pass(source: &Image, pass: usize) -> Image {
+    let (width, height) = pass_size(pass, source.width, source.height);
+    let (x_start, y_start, x_step, y_step) = ADAM7[pass];
+    let bits = source.bits_per_pixel();
+    let row_bytes = image::row_bytes(width, bits);
+    let mut pixels = vec![0u8; height as usize * row_bytes];
+    for y in 0..height {
+        let source_row = source.row(y_start + y * y_step);
+        let target_row = &mut pixels[y as usize * row_bytes..(y as usize + 1) * row_bytes];
+        for x in 0..width {
+            copy_pixel(source_row, (x_start + x * x_step) as usize, target_row, x as usize, bits);
+        }
+    }
+    Image {
+        width,
+        height,
+        color_type: source.color_type,
+        bit_depth: source.bit_depth,
+        pixels,
+    }
+}
+
+// 把完整图像按 Adam7 拆成七遍，逐遍过滤后拼接为未压缩的扫描行数据
+pub(crate) fn filter_passes(source: &Image, options: &EncodeOptions) -> Result<Vec<u8>> {
+    let bpp = image::filter_bpp(source.bits_per_pixel());
+    let mut data = Vec::new();
+    for pass in 0..ADAM7.len() {
+        let reduced = gather_pass(source, pass);
+        if reduced.width == 0 || reduced.height == 0 {
+            continue;
+        }
+        let filtered = image::filter_scanlines(&reduced.pixels, reduced.height as usize, reduced.row_bytes(), bpp, options)?;
+        data.extend_from_slice(&filtered);
+    }
+    Ok(data)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::ColorType;
+
+    fn test_image(width: u32, height: u32, color_type: ColorType, bit_depth: u8) -> Image {
+        let row_bytes = image::row_bytes(width, color_type.channels() * bit_depth as usize);
+        let pixels = (0..height as usize * row_bytes).map(|i| (i * 37 % 251) as u8).collect::<Vec<u8>>();
+        let mut image = Image {
+            width,
+            height,
+            color_type,
+            bit_depth,
+            pixels,
+        };
+        // 清除每行末尾的填充位，使其与解码结果一致
+        let bits = image.bits_per_pixel() * width as usize;
+        if !bits.is_multiple_of(8) {
+            let mask = 0xFFu8 << (8 - bits % 8);
+            for y in 0..height as usize {
+                image.pixels[(y + 1) * row_bytes - 1] &= mask;
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn test_pass_sizes() {
+        let sizes: Vec<(u32, u32)> = (0..7).map(|p| pass_size(p, 8, 8)).collect();
+        assert_eq!(sizes, vec![(1, 1), (1, 1), (2, 1), (2, 2), (4, 2), (4, 4), (8, 4)]);
+        assert_eq!(pass_size(1, 3, 3), (0, 1));
+        assert_eq!(pass_size(6, 1, 1), (1, 0));
+    }
+
+    #[test]
+    fn test_copy_packed_pixel() {
+        let source = [0b0110_0000];
+        let mut target = [0b1111_1111];
+        copy_pixel(&source, 1, &mut target, 3, 2);
+        assert_eq!(target, [0b1111_1110]);
+    }
+
+    #[test]
+    fn test_interlace_round_trip() {
+        let cases = [
+            (13, 7, ColorType::Rgb, 8),
+            (5, 11, ColorType::Grayscale, 1),
+            (9, 9, ColorType::Indexed, 4),
+            (3, 2, ColorType::Rgba, 16),
+            (1, 1, ColorType::GrayscaleAlpha, 8),
+        ];
+        for (width, height, color_type, bit_depth) in cases {
+            let source = test_image(width, height, color_type, bit_depth);
+            let data = filter_passes(&source, &EncodeOptions::default()).unwrap();
+            let ihdr = Ihdr::new(width, height, bit_depth, color_type, InterlaceMethod::Adam7).unwrap();
+            let decoded = decode(&ihdr, &image::deflate(&data, 6).unwrap()).unwrap();
+            assert_eq!(decoded, source);
+        }
+    }
+
+    #[test]
+    fn test_passes_skip_empty() {
+        let source = test_image(1, 1, ColorType::Grayscale, 8);
+        let data = filter_passes(&source, &EncodeOptions::default()).unwrap();
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Grayscale, InterlaceMethod::Adam7).unwrap();
+        let passes: Vec<Pass> = Passes::new(ihdr, &image::deflate(&data, 6).unwrap())
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(passes.len(), 1);
+        assert_eq!(passes[0].index, 1);
+    }
+
+    #[test]
+    fn test_passes_non_interlaced() {
+        let source = test_image(4, 4, ColorType::Rgb, 8);
+        let data = image::filter_scanlines(&source.pixels, 4, source.row_bytes(), 3, &EncodeOptions::default()).unwrap();
+        let ihdr = Ihdr::new(4, 4, 8, ColorType::Rgb, InterlaceMethod::None).unwrap();
+        let passes: Vec<Pass> = Passes::new(ihdr, &image::deflate(&data, 6).unwrap())
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(passes.len(), 1);
+        assert_eq!(passes[0].index, 0);
+        assert_eq!(passes[0].image, source);
+    }
+}
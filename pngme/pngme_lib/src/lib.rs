@@ -1,10 +1,52 @@
 // use std::str::FromStr;
 // use anyhow::Result;
 
+mod ancillary;
+mod apng;
+mod attachment;
 mod chunk;
+mod chunk_ref;
 mod chunk_type;
+mod codec;
+mod crypto;
+mod diff;
+mod error;
+mod filter;
+mod ihdr;
+mod image;
+mod interlace;
+mod palette;
+mod parse;
+mod payload;
 mod png;
+mod png_ref;
+mod reader;
+mod repair;
+mod text;
+mod validate;
+mod writer;
 
+pub use ancillary::{Background, KnownChunk, PhysicalUnit, RenderingIntent};
+pub use apng::{AnimationControl, BlendOp, DisposeOp, Frame, FrameControl, FrameData, Frames};
+pub use attachment::{guess_mime_type, Attachment};
 pub use chunk::Chunk;
+pub use chunk_ref::ChunkRef;
 pub use chunk_type::ChunkType;
-pub use png::Png;
\ No newline at end of file
+pub use codec::{ChunkCodec, ChunkRegistry};
+pub use crypto::{decrypt, encrypt, encrypt_with_params, KdfParams};
+pub use diff::{ByteDiff, ChunkChange};
+pub use error::{PngError, Result};
+pub use filter::FilterType;
+pub use ihdr::{ColorType, Ihdr, InterlaceMethod};
+pub use image::{EncodeOptions, FilterStrategy, Image};
+pub use interlace::{Pass, Passes};
+pub use palette::{Palette, Transparency};
+pub use parse::{ErrorAction, ParseIssue, ParseOptions, ParseReport};
+pub use payload::{join_payload, split_payload};
+pub use png::Png;
+pub use png_ref::PngRef;
+pub use reader::PngReader;
+pub use repair::Repair;
+pub use text::{CompressedText, InternationalText, Text, TextEntry};
+pub use validate::{Diagnostic, Severity};
+pub use writer::PngWriter;
\ No newline at end of file
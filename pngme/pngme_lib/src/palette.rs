@@ -0,0 +1,279 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::chunk::Chunk;
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::codec::ChunkCodec;
+use crate::error::{PngError, Result};
+use crate::ihdr::{ColorType, Ihdr};
+
+// 调色板最多包含的颜色数
+const MAX_ENTRIES: usize = 256;
+
+fn invalid(message: String) -> PngError {
+    PngError::InvalidPalette(message)
+}
+
+// PLTE 数据块：按下标排列的 RGB 颜色
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    pub entries: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    // 创建调色板，颜色数必须在 1 到 256 之间
+    pub fn new(entries: Vec<[u8; 3]>) -> Result<Palette> {
+        if entries.is_empty() || entries.len() > MAX_ENTRIES {
+            return Err(invalid(format!("Palette must have 1 to {} entries, found {}", MAX_ENTRIES, entries.len())));
+        }
+        Ok(Palette { entries })
+    }
+
+    // 返回 PLTE 数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("PLTE").unwrap()
+    }
+
+    // 检查调色板是否允许用于该颜色类型，以及索引色图像的颜色数是否超出位深度能表示的范围
+    pub fn validate(&self, ihdr: &Ihdr) -> Result<()> {
+        match ihdr.color_type {
+            ColorType::Grayscale | ColorType::GrayscaleAlpha => {
+                Err(invalid(format!("PLTE is not allowed for color type {}", ihdr.color_type)))
+            }
+            ColorType::Indexed if self.entries.len() > 1 << ihdr.bit_depth => Err(invalid(format!(
+                "{} palette entries exceed bit depth {}",
+                self.entries.len(),
+                ihdr.bit_depth
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // 查找下标对应的 RGBA 颜色，透明度取自 tRNS，没有时为不透明；下标超出范围时返回 None
+    pub fn lookup(&self, index: u8, transparency: Option<&Transparency>) -> Option<[u8; 4]> {
+        let [r, g, b] = *self.entries.get(index as usize)?;
+        let alpha = match transparency {
+            Some(Transparency::Alpha(alpha)) => alpha.get(index as usize).copied().unwrap_or(255),
+            _ => 255,
+        };
+        Some([r, g, b, alpha])
+    }
+
+    // 把整个调色板展开为 RGBA 颜色
+    pub fn to_rgba(&self, transparency: Option<&Transparency>) -> Vec<[u8; 4]> {
+        (0..self.entries.len())
+            .map(|index| self.lookup(index as u8, transparency).unwrap())
+            .collect()
+    }
+
+    // 按 RGB 三元组依次存放的字节，与 EncodeOptions::palette 的格式相同
+    pub fn as_bytes(&self) -> Vec<u8> {
+        self.entries.concat()
+    }
+
+    // 编码为 PLTE 数据块
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(Self::chunk_type(), self.as_bytes())
+    }
+}
+
+impl TryFrom<&[u8]> for Palette {
+    type Error = PngError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        if !bytes.len().is_multiple_of(3) {
+            return Err(invalid(format!("PLTE length {} is not a multiple of 3", bytes.len())));
+        }
+        Palette::new(bytes.chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect())
+    }
+}
+
+impl TryFrom<&ChunkRef<'_>> for Palette {
+    type Error = PngError;
+
+    fn try_from(chunk: &ChunkRef<'_>) -> Result<Self> {
+        if *chunk.chunk_type() != Self::chunk_type() {
+            return Err(invalid(format!("Expected PLTE chunk, found {}", chunk.chunk_type())));
+        }
+        Palette::try_from(chunk.data())
+    }
+}
+
+impl TryFrom<&Chunk> for Palette {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        Palette::try_from(&ChunkRef::from(chunk))
+    }
+}
+
+impl ChunkCodec for Palette {
+    fn chunk_type() -> ChunkType {
+        Palette::chunk_type()
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        Palette::try_from(data)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        Ok(self.as_bytes())
+    }
+}
+
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} entries", self.entries.len())
+    }
+}
+
+// tRNS 数据块，格式取决于 IHDR 的颜色类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transparency {
+    // 索引色图像：调色板前若干项的透明度，其余项不透明
+    Alpha(Vec<u8>),
+    // 灰度图像：完全透明的灰度值
+    Gray(u16),
+    // 真彩色图像：完全透明的颜色
+    Rgb(u16, u16, u16),
+}
+
+impl Transparency {
+    // 返回 tRNS 数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("tRNS").unwrap()
+    }
+
+    // 按颜色类型解析 tRNS 数据，带 alpha 通道的颜色类型不允许使用 tRNS
+    pub fn parse(data: &[u8], ihdr: &Ihdr) -> Result<Transparency> {
+        let sample = |offset: usize| u16::from_be_bytes([data[offset], data[offset + 1]]);
+        let expect_length = |length: usize| {
+            if data.len() != length {
+                return Err(invalid(format!("Invalid tRNS length {} for color type {}", data.len(), ihdr.color_type)));
+            }
+            Ok(())
+        };
+        match ihdr.color_type {
+            ColorType::Indexed => {
+                if data.len() > MAX_ENTRIES {
+                    return Err(invalid(format!("tRNS has {} entries, at most {} allowed", data.len(), MAX_ENTRIES)));
+                }
+                Ok(Transparency::Alpha(data.to_vec()))
+            }
+            ColorType::Grayscale => {
+                expect_length(2)?;
+                Ok(Transparency::Gray(sample(0)))
+            }
+            ColorType::Rgb => {
+                expect_length(6)?;
+                Ok(Transparency::Rgb(sample(0), sample(2), sample(4)))
+            }
+            color_type => Err(invalid(format!("tRNS is not allowed for color type {}", color_type))),
+        }
+    }
+
+    // 检查透明色是否超出位深度，以及索引色图像的透明度项数是否超过调色板颜色数
+    pub fn validate(&self, ihdr: &Ihdr, palette: Option<&Palette>) -> Result<()> {
+        let max_sample = ((1u32 << ihdr.bit_depth) - 1) as u16;
+        match self {
+            Transparency::Alpha(alpha) => match palette {
+                Some(palette) if alpha.len() > palette.entries.len() => Err(invalid(format!(
+                    "tRNS has {} entries but the palette has only {}",
+                    alpha.len(),
+                    palette.entries.len()
+                ))),
+                _ => Ok(()),
+            },
+            Transparency::Gray(gray) if *gray > max_sample => {
+                Err(invalid(format!("tRNS gray level {} exceeds bit depth {}", gray, ihdr.bit_depth)))
+            }
+            Transparency::Rgb(r, g, b) if [r, g, b].iter().any(|&&v| v > max_sample) => {
+                Err(invalid(format!("tRNS color exceeds bit depth {}", ihdr.bit_depth)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // 编码后的 tRNS 数据
+    pub fn as_bytes(&self) -> Vec<u8> {
+        match self {
+            Transparency::Alpha(alpha) => alpha.clone(),
+            Transparency::Gray(gray) => gray.to_be_bytes().to_vec(),
+            Transparency::Rgb(r, g, b) => [r.to_be_bytes(), g.to_be_bytes(), b.to_be_bytes()].concat(),
+        }
+    }
+
+    // 编码为 tRNS 数据块
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(Self::chunk_type(), self.as_bytes())
+    }
+}
+
+impl fmt::Display for Transparency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transparency::Alpha(alpha) => write!(f, "alpha for {} palette entries", alpha.len()),
+            Transparency::Gray(gray) => write!(f, "transparent gray {}", gray),
+            Transparency::Rgb(r, g, b) => write!(f, "transparent rgb({}, {}, {})", r, g, b),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::InterlaceMethod;
+
+    fn ihdr(bit_depth: u8, color_type: ColorType) -> Ihdr {
+        Ihdr::new(1, 1, bit_depth, color_type, InterlaceMethod::None).unwrap()
+    }
+
+    #[test]
+    fn test_palette_from_bytes() {
+        let palette = Palette::try_from([255, 0, 0, 0, 255, 0].as_slice()).unwrap();
+        assert_eq!(palette.entries, [[255, 0, 0], [0, 255, 0]]);
+        assert_eq!(Palette::try_from(&palette.to_chunk()).unwrap(), palette);
+        assert!(Palette::try_from([1, 2].as_slice()).is_err());
+        assert!(Palette::try_from([].as_slice()).is_err());
+        assert!(Palette::try_from(vec![0; 257 * 3].as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_palette_validate() {
+        let palette = Palette::new(vec![[0, 0, 0]; 5]).unwrap();
+        assert!(palette.validate(&ihdr(4, ColorType::Indexed)).is_ok());
+        assert!(palette.validate(&ihdr(2, ColorType::Indexed)).is_err());
+        assert!(palette.validate(&ihdr(8, ColorType::Rgb)).is_ok());
+        assert!(palette.validate(&ihdr(8, ColorType::Grayscale)).is_err());
+    }
+
+    #[test]
+    fn test_lookup_with_transparency() {
+        let palette = Palette::new(vec![[10, 20, 30], [40, 50, 60], [70, 80, 90]]).unwrap();
+        let transparency = Transparency::parse(&[0, 128], &ihdr(8, ColorType::Indexed)).unwrap();
+        assert_eq!(
+            palette.to_rgba(Some(&transparency)),
+            [[10, 20, 30, 0], [40, 50, 60, 128], [70, 80, 90, 255]]
+        );
+        assert_eq!(palette.lookup(1, None), Some([40, 50, 60, 255]));
+        assert_eq!(palette.lookup(3, None), None);
+        let too_many = Transparency::Alpha(vec![0; 4]);
+        assert!(too_many.validate(&ihdr(8, ColorType::Indexed), Some(&palette)).is_err());
+    }
+
+    #[test]
+    fn test_transparency_by_color_type() {
+        let gray = Transparency::parse(&[0, 15], &ihdr(4, ColorType::Grayscale)).unwrap();
+        assert_eq!(gray, Transparency::Gray(15));
+        assert!(gray.validate(&ihdr(4, ColorType::Grayscale), None).is_ok());
+        assert!(Transparency::Gray(16).validate(&ihdr(4, ColorType::Grayscale), None).is_err());
+        let rgb = Transparency::parse(&[0, 1, 0, 2, 0, 3], &ihdr(8, ColorType::Rgb)).unwrap();
+        assert_eq!(rgb, Transparency::Rgb(1, 2, 3));
+        assert_eq!(rgb.as_bytes(), [0, 1, 0, 2, 0, 3]);
+        assert!(Transparency::parse(&[0, 1], &ihdr(8, ColorType::Rgb)).is_err());
+        assert!(Transparency::parse(&[0, 1], &ihdr(8, ColorType::Rgba)).is_err());
+    }
+}
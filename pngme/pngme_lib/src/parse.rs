@@ -0,0 +1,77 @@
+use std::fmt;
+
+use crate::error::PngError;
+
+// 解析时遇到某类问题后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    // 立即返回错误，停止解析
+    Error,
+    // 记录到解析报告中并继续
+    Warn,
+    // 不记录，直接继续
+    Ignore,
+}
+
+// 宽松解析的选项，默认与严格解析一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    // CRC 校验失败时：保留数据块及其原有的 CRC
+    pub on_crc_error: ErrorAction,
+    // 输入提前结束时：保留已读到的部分数据并结束解析
+    pub on_truncation: ErrorAction,
+    // 数据块类型无效时：按长度字段跳过该数据块，跳过后不是输入结尾或有效的数据块头部时停止解析
+    pub on_invalid_chunk: ErrorAction,
+}
+
+impl ParseOptions {
+    // 所有问题都记录后继续的选项
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            on_crc_error: ErrorAction::Warn,
+            on_truncation: ErrorAction::Warn,
+            on_invalid_chunk: ErrorAction::Warn,
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            on_crc_error: ErrorAction::Error,
+            on_truncation: ErrorAction::Error,
+            on_invalid_chunk: ErrorAction::Error,
+        }
+    }
+}
+
+// 解析过程中被跳过或修正的一个问题，offset 为出问题的数据块在输入中的起始位置
+// chunk_index 为该数据块在恢复结果中的下标，数据块被丢弃时为 None
+#[derive(Debug)]
+pub struct ParseIssue {
+    pub offset: u64,
+    pub chunk_index: Option<usize>,
+    pub error: PngError,
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset {}: {}", self.offset, self.error)
+    }
+}
+
+// 宽松解析的结果报告
+#[derive(Debug, Default)]
+pub struct ParseReport {
+    // 按出现顺序记录的问题
+    pub issues: Vec<ParseIssue>,
+    // 实际读取的字节数（含文件头）
+    pub bytes_read: u64,
+}
+
+impl ParseReport {
+    // 是否没有记录任何问题
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
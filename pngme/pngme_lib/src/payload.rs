@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use crate::chunk::{self, Chunk};
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+
+// 分段格式的版本
+const FORMAT_VERSION: u8 = 1;
+// 分段头部：版本(1) + 整体校验值(4) + 段序号(4) + 段总数(4)
+const HEADER_LENGTH: usize = 13;
+// 单个数据块允许的最大数据长度
+const MAX_CHUNK_LENGTH: usize = i32::MAX as usize;
+// 缺失段过多时错误信息中最多列出的序号个数
+const MAX_LISTED_MISSING: usize = 10;
+
+// 把大段数据拆分为多个同类型数据块，每段数据不超过 segment_size 字节
+// 每个数据块带有序号和总数，并记录整体数据的 CRC 以便重组后校验
+pub fn split_payload(chunk_type: ChunkType, data: &[u8], segment_size: usize) -> Result<Vec<Chunk>> {
+    if segment_size == 0 || segment_size > MAX_CHUNK_LENGTH - HEADER_LENGTH {
+        return Err(PngError::InvalidPayload(format!("Invalid segment size: {}", segment_size)));
+    }
+    let segments: Vec<&[u8]> = if data.is_empty() {
+        vec![&[]]
+    } else {
+        data.chunks(segment_size).collect()
+    };
+    let total = u32::try_from(segments.len()).map_err(|_| PngError::InvalidPayload("Payload has too many segments".to_string()))?;
+    let checksum = chunk::checksum(&chunk_type, data);
+    let chunks = segments
+        .iter()
+        .enumerate()
+        .map(|(index, segment)| {
+            let mut bytes = Vec::with_capacity(HEADER_LENGTH + segment.len());
+            bytes.push(FORMAT_VERSION);
+            bytes.extend_from_slice(&checksum.to_be_bytes());
+            bytes.extend_from_slice(&(index as u32).to_be_bytes());
+            bytes.extend_from_slice(&total.to_be_bytes());
+            bytes.extend_from_slice(segment);
+            Chunk::new(chunk_type, bytes)
+        })
+        .collect();
+    Ok(chunks)
+}
+
+// 把分段数据块按序号重组为完整数据，检测缺失、重复、总数不一致以及校验值错误
+pub fn join_payload<'a, I>(chunks: I) -> Result<Vec<u8>>
+where
+    I: IntoIterator<Item = &'a Chunk>,
+{
+    let mut header: Option<(ChunkType, u32, u32)> = None;
+    // 段总数来自不可信的数据，只记录实际出现的段，不按总数预先分配
+    let mut segments: BTreeMap<u32, &[u8]> = BTreeMap::new();
+    for chunk in chunks {
+        let data = chunk.data();
+        if data.len() < HEADER_LENGTH {
+            return Err(PngError::InvalidPayload("Segment is too short".to_string()));
+        }
+        if data[0] != FORMAT_VERSION {
+            return Err(PngError::InvalidPayload(format!("Unsupported segment format version: {}", data[0])));
+        }
+        let checksum = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+        let index = u32::from_be_bytes([data[5], data[6], data[7], data[8]]);
+        let total = u32::from_be_bytes([data[9], data[10], data[11], data[12]]);
+        match header {
+            None => {
+                if total == 0 {
+                    return Err(PngError::InvalidPayload("Segment total must be at least 1".to_string()));
+                }
+                header = Some((*chunk.chunk_type(), checksum, total));
+            }
+            Some((chunk_type, expected_checksum, expected_total)) => {
+                if chunk_type != *chunk.chunk_type() || expected_checksum != checksum || expected_total != total {
+                    return Err(PngError::InvalidPayload("Segments belong to different payloads".to_string()));
+                }
+            }
+        }
+        if index >= total {
+            return Err(PngError::InvalidPayload(format!("Segment {} is out of range for {} segments", index, total)));
+        }
+        if segments.insert(index, &data[HEADER_LENGTH..]).is_some() {
+            return Err(PngError::InvalidPayload(format!("Duplicate segment {}", index)));
+        }
+    }
+
+    let (chunk_type, checksum, total) = header.ok_or_else(|| PngError::InvalidPayload("No segments found".to_string()))?;
+    let missing_count = total as usize - segments.len();
+    if missing_count > 0 {
+        let mut missing: Vec<String> = (0..total)
+            .filter(|index| !segments.contains_key(index))
+            .take(MAX_LISTED_MISSING)
+            .map(|index| index.to_string())
+            .collect();
+        if missing_count > MAX_LISTED_MISSING {
+            missing.push(format!("... ({} in total)", missing_count));
+        }
+        return Err(PngError::InvalidPayload(format!("Missing segments: {}", missing.join(", "))));
+    }
+    let payload: Vec<u8> = segments.into_values().flatten().copied().collect();
+    if chunk::checksum(&chunk_type, &payload) != checksum {
+        return Err(PngError::InvalidPayload("Payload checksum mismatch".to_string()));
+    }
+    Ok(payload)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn payload() -> Vec<u8> {
+        (0..1000).map(|i| (i % 256) as u8).collect()
+    }
+
+    fn segments() -> Vec<Chunk> {
+        split_payload(ChunkType::from_str("ruSt").unwrap(), &payload(), 300).unwrap()
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        let chunks = segments();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[3].length() as usize, HEADER_LENGTH + 100);
+        assert_eq!(join_payload(&chunks).unwrap(), payload());
+    }
+
+    #[test]
+    fn test_join_out_of_order() {
+        let mut chunks = segments();
+        chunks.reverse();
+        assert_eq!(join_payload(&chunks).unwrap(), payload());
+    }
+
+    #[test]
+    fn test_join_missing_segment() {
+        let mut chunks = segments();
+        chunks.remove(1);
+        let err = join_payload(&chunks).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid payload: Missing segments: 1");
+    }
+
+    // 把每个分段头部中的段总数改为 total
+    fn with_total(total: u32) -> Vec<Chunk> {
+        segments()
+            .iter()
+            .map(|chunk| {
+                let mut data = chunk.data().to_vec();
+                data[9..13].copy_from_slice(&total.to_be_bytes());
+                Chunk::new(*chunk.chunk_type(), data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_join_invalid_total() {
+        let err = join_payload(&with_total(0)).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid payload: Segment total must be at least 1");
+        // 巨大的段总数不会按总数分配内存，只列出前几个缺失的序号
+        let err = join_payload(&with_total(u32::MAX)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid payload: Missing segments: 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, ... ({} in total)", u32::MAX - 4)
+        );
+    }
+
+    #[test]
+    fn test_join_duplicate_segment() {
+        let mut chunks = segments();
+        chunks.push(chunks[2].clone());
+        let err = join_payload(&chunks).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid payload: Duplicate segment 2");
+    }
+
+    #[test]
+    fn test_join_mixed_payloads() {
+        let mut chunks = segments();
+        let other = split_payload(ChunkType::from_str("ruSt").unwrap(), b"other", 300).unwrap();
+        chunks.extend(other);
+        assert!(join_payload(&chunks).is_err());
+    }
+
+    #[test]
+    fn test_empty_payload() {
+        let chunks = split_payload(ChunkType::from_str("ruSt").unwrap(), &[], 10).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert!(join_payload(&chunks).unwrap().is_empty());
+        assert!(join_payload(&[]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_segment_size() {
+        assert!(split_payload(ChunkType::from_str("ruSt").unwrap(), b"data", 0).is_err());
+    }
+}
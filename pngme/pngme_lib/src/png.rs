@@ -1,9 +1,26 @@
 use std::convert::TryFrom;
 use std::fmt;
-use anyhow::{Result, Error};
 use std::str::FromStr;
+use std::io::{Read, Write};
+use crate::apng::{self, AnimationControl, FrameControl, Frames};
+use crate::attachment::Attachment;
 use crate::chunk::Chunk;
+use crate::chunk_ref::ChunkRef;
 use crate::chunk_type::ChunkType;
+use crate::codec::{ChunkCodec, ChunkRegistry};
+use crate::diff::{self, ChunkChange};
+use crate::error::{PngError, Result};
+use crate::ihdr::{ColorType, Ihdr};
+use crate::image::{self, EncodeOptions, Image};
+use crate::interlace::Passes;
+use crate::palette::{Palette, Transparency};
+use crate::parse::{ParseOptions, ParseReport};
+use crate::payload;
+use crate::repair::{self, Repair};
+use crate::text::TextEntry;
+use crate::validate::{self, Diagnostic};
+use crate::reader::PngReader;
+use crate::writer::PngWriter;
 
 pub struct Png {
     header: [u8; 8],
@@ -22,21 +39,78 @@ impl Png {
         }
     }
 
+    // 从任意读取源流式解析 PNG 文件
+    pub fn from_reader<R: Read>(reader: R) -> Result<Png> {
+        let chunks = PngReader::new(reader)?.collect::<Result<Vec<Chunk>>>()?;
+        Ok(Png::from_chunks(chunks))
+    }
+
+    // 按选项宽松地解析 PNG，返回恢复出的数据块以及记录的问题
+    pub fn parse_with<R: Read>(reader: R, options: ParseOptions) -> Result<(Png, ParseReport)> {
+        let mut reader = PngReader::with_options(reader, options)?;
+        let chunks = reader.by_ref().collect::<Result<Vec<Chunk>>>()?;
+        Ok((Png::from_chunks(chunks), reader.into_report()))
+    }
+
+    // 宽松解析后修复 CRC 错误、截断和缺失的 IEND，并丢弃无法识别的数据，返回修复后的 PNG 与全部改动
+    pub fn repair<R: Read>(reader: R) -> Result<(Png, Vec<Repair>)> {
+        let (mut png, report) = Png::parse_with(reader, ParseOptions::lenient())?;
+        let repairs = repair::repair(&mut png.chunks, &report);
+        Ok((png, repairs))
+    }
+
     // 向 Png 实例中追加一个数据块
     pub fn append_chunk(&mut self, chunk: Chunk) {
         self.chunks.push(chunk);
     }
 
+    // 在指定位置插入数据块，位置超出范围时返回错误
+    pub fn insert_chunk(&mut self, index: usize, chunk: Chunk) -> Result<()> {
+        if index > self.chunks.len() {
+            return Err(PngError::IndexOutOfRange {
+                index,
+                len: self.chunks.len(),
+            });
+        }
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    // 在第一个指定类型的数据块之前插入数据块
+    pub fn insert_before(&mut self, chunk_type: &str, chunk: Chunk) -> Result<()> {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        match self.chunks.iter().position(|c| *c.chunk_type() == chunk_type) {
+            Some(index) => self.insert_chunk(index, chunk),
+            None => Err(PngError::ChunkNotFound(chunk_type)),
+        }
+    }
+
+    // 在最后一个指定类型的数据块之后插入数据块（例如插在全部 IDAT 之后）
+    pub fn insert_after(&mut self, chunk_type: &str, chunk: Chunk) -> Result<()> {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        match self.chunks.iter().rposition(|c| *c.chunk_type() == chunk_type) {
+            Some(index) => self.insert_chunk(index + 1, chunk),
+            None => Err(PngError::ChunkNotFound(chunk_type)),
+        }
+    }
+
     // 移除第一个指定类型的数据块
     pub fn remove_first_chunk(&mut self, chunk_type: &str) -> Result<Chunk> {
         let chunk_type = ChunkType::from_str(chunk_type)?;
         if let Some(index) = self.chunks.iter().position(|c| *c.chunk_type() == chunk_type) {
             Ok(self.chunks.remove(index))
         } else {
-            Err(Error::msg(format!("Chunk of type {} not found", chunk_type)))
+            Err(PngError::ChunkNotFound(chunk_type))
         }
     }
 
+    // 只保留 keep 返回 true 的数据块，按原顺序返回被移除的数据块
+    pub fn retain<F: FnMut(&Chunk) -> bool>(&mut self, mut keep: F) -> Vec<Chunk> {
+        let (kept, removed) = std::mem::take(&mut self.chunks).into_iter().partition(|c| keep(c));
+        self.chunks = kept;
+        removed
+    }
+
     // 返回 PNG 文件头的引用
     pub fn header(&self) -> &[u8; 8] {
         &self.header
@@ -47,6 +121,11 @@ impl Png {
         &self.chunks
     }
 
+    // 返回每个数据块按 as_bytes 写出时的起始字节偏移，与 chunks 一一对应
+    pub fn chunk_offsets(&self) -> Vec<u64> {
+        (0..self.chunks.len()).map(|index| validate::chunk_offset(&self.chunks, index)).collect()
+    }
+
     // 根据数据块类型查找数据块
     pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
         let chunk_type = match ChunkType::from_str(chunk_type) {
@@ -56,6 +135,205 @@ impl Png {
         self.chunks.iter().find(|c| *c.chunk_type() == chunk_type)
     }
 
+    // 返回所有指定类型的数据块，类型无效时返回空列表
+    pub fn chunks_by_type(&self, chunk_type: &str) -> Vec<&Chunk> {
+        match ChunkType::from_str(chunk_type) {
+            Ok(ct) => self.chunks.iter().filter(|c| *c.chunk_type() == ct).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    // 重组由 split_payload 拆分到多个同类型数据块中的数据
+    pub fn read_payload(&self, chunk_type: &str) -> Result<Vec<u8>> {
+        payload::join_payload(self.chunks_by_type(chunk_type))
+    }
+
+    // 解析所有附件数据块
+    pub fn attachments(&self) -> Result<Vec<Attachment>> {
+        self.decode_chunks()
+    }
+
+    // 用 T 的编解码器解码第一个 T 类型的数据块
+    pub fn decode_chunk<T: ChunkCodec>(&self) -> Result<T> {
+        let chunk_type = T::chunk_type();
+        match self.chunks.iter().find(|c| *c.chunk_type() == chunk_type) {
+            Some(chunk) => T::decode(chunk.data()),
+            None => Err(PngError::ChunkNotFound(chunk_type)),
+        }
+    }
+
+    // 按顺序解码所有 T 类型的数据块
+    pub fn decode_chunks<T: ChunkCodec>(&self) -> Result<Vec<T>> {
+        let chunk_type = T::chunk_type();
+        self.chunks
+            .iter()
+            .filter(|c| *c.chunk_type() == chunk_type)
+            .map(|c| T::decode(c.data()))
+            .collect()
+    }
+
+    // 编码 value 并替换第一个同类型的数据块，同时删除其余同类型的数据块；没有时插在 IEND 之前
+    pub fn put_chunk<T: ChunkCodec>(&mut self, value: &T) -> Result<()> {
+        let chunk = Chunk::new(T::chunk_type(), value.encode()?);
+        let position = self.chunks.iter().position(|c| c.chunk_type() == chunk.chunk_type());
+        self.chunks.retain(|c| c.chunk_type() != chunk.chunk_type());
+        let index = position.unwrap_or_else(|| self.iend_position());
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    // 按顺序描述每个数据块：已注册的类型用编解码器格式化，未注册或解码失败时使用原始格式
+    pub fn describe_chunks(&self, registry: &ChunkRegistry) -> Vec<String> {
+        self.chunks
+            .iter()
+            .map(|chunk| match registry.describe(&ChunkRef::from(chunk)) {
+                Some(Ok(description)) => description,
+                _ => chunk.to_string(),
+            })
+            .collect()
+    }
+
+    // 第一个 IEND 的下标，没有 IEND 时返回数据块数量
+    fn iend_position(&self) -> usize {
+        let iend = self.chunks.iter().position(|c| c.chunk_type().to_string() == "IEND");
+        iend.unwrap_or(self.chunks.len())
+    }
+
+    // 按顺序解析全部 tEXt、zTXt 和 iTXt 文本元数据，每个文本数据块对应一个结果
+    // 某个数据块格式错误时只有该项为错误，其余文本仍可读取
+    pub fn text_entries(&self) -> Vec<Result<TextEntry>> {
+        self.chunks
+            .iter()
+            .filter(|c| TextEntry::CHUNK_TYPES.contains(&c.chunk_type().to_string().as_str()))
+            .map(TextEntry::try_from)
+            .collect()
+    }
+
+    // 设置关键字对应的文本，数据块类型由 TextEntry::new 按内容选择
+    // 替换第一个同关键字的文本数据块并删除其余的；没有时插在 IEND 之前
+    pub fn set_text(&mut self, keyword: &str, value: &str) -> Result<()> {
+        let chunk = TextEntry::new(keyword, value)?.to_chunk()?;
+        let matches = |c: &Chunk| TextEntry::try_from(c).is_ok_and(|entry| entry.keyword() == keyword);
+        let position = self.chunks.iter().position(matches);
+        self.chunks.retain(|c| !matches(c));
+        let index = position.unwrap_or_else(|| self.iend_position());
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    // 解析第一个数据块得到图像头信息
+    pub fn ihdr(&self) -> Result<Ihdr> {
+        match self.chunks.first() {
+            Some(chunk) => Ihdr::try_from(chunk),
+            None => Err(PngError::ChunkNotFound(Ihdr::chunk_type())),
+        }
+    }
+
+    // 解析 PLTE 调色板并按 IHDR 检查颜色数
+    pub fn palette(&self) -> Result<Palette> {
+        let palette = match self.chunk_by_type("PLTE") {
+            Some(chunk) => Palette::try_from(chunk)?,
+            None => return Err(PngError::ChunkNotFound(Palette::chunk_type())),
+        };
+        palette.validate(&self.ihdr()?)?;
+        Ok(palette)
+    }
+
+    // 按 IHDR 的颜色类型解析 tRNS，并检查透明色的取值范围和透明度项数
+    pub fn transparency(&self) -> Result<Transparency> {
+        let ihdr = self.ihdr()?;
+        let transparency = match self.chunk_by_type("tRNS") {
+            Some(chunk) => Transparency::parse(chunk.data(), &ihdr)?,
+            None => return Err(PngError::ChunkNotFound(Transparency::chunk_type())),
+        };
+        let palette = match self.palette() {
+            Ok(palette) => Some(palette),
+            Err(PngError::ChunkNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        transparency.validate(&ihdr, palette.as_ref())?;
+        Ok(transparency)
+    }
+
+    // 解码图像并展开为每像素 4 字节的 RGBA8，索引色通过调色板查找颜色，tRNS 用于计算透明度
+    pub fn decode_rgba8(&self) -> Result<Vec<u8>> {
+        let image = self.decode_image()?;
+        let palette = match self.palette() {
+            Ok(palette) => Some(palette.as_bytes()),
+            Err(PngError::ChunkNotFound(_)) if image.color_type != ColorType::Indexed => None,
+            Err(e) => return Err(e),
+        };
+        let transparency = match self.transparency() {
+            Ok(transparency) => Some(transparency.as_bytes()),
+            Err(PngError::ChunkNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        image.to_rgba8(palette.as_deref(), transparency.as_deref())
+    }
+
+    // 拼接所有 IDAT 数据块的数据
+    fn image_data(&self) -> Result<Vec<u8>> {
+        let idat = ChunkType::from_str("IDAT")?;
+        let mut compressed = Vec::new();
+        for chunk in self.chunks.iter().filter(|c| *c.chunk_type() == idat) {
+            compressed.extend_from_slice(chunk.data());
+        }
+        if compressed.is_empty() {
+            return Err(PngError::ChunkNotFound(idat));
+        }
+        Ok(compressed)
+    }
+
+    // 拼接所有 IDAT 数据块并解码为像素数据
+    pub fn decode_image(&self) -> Result<Image> {
+        let ihdr = self.ihdr()?;
+        image::decode(&ihdr, &self.image_data()?)
+    }
+
+    // 逐遍解码图像，隔行图像依次产出 Adam7 的七遍，非隔行图像只产出一遍
+    pub fn progressive_passes(&self) -> Result<Passes> {
+        let ihdr = self.ihdr()?;
+        Passes::new(ihdr, &self.image_data()?)
+    }
+
+    // 返回 APNG 的 acTL 动画控制信息，非动画 PNG 返回 ChunkNotFound
+    pub fn animation_control(&self) -> Result<AnimationControl> {
+        match self.chunk_by_type("acTL") {
+            Some(chunk) => AnimationControl::try_from(chunk),
+            None => Err(PngError::ChunkNotFound(AnimationControl::chunk_type())),
+        }
+    }
+
+    // 按顺序返回每一帧的 fcTL，并校验 fcTL/fdAT 的序号、帧数以及帧区域
+    pub fn frame_controls(&self) -> Result<Vec<FrameControl>> {
+        let (_, frames) = apng::parse_frames(&self.ihdr()?, &self.chunks)?;
+        Ok(frames.into_iter().map(|frame| frame.control).collect())
+    }
+
+    // 按顺序解码并合成 APNG 的每一帧，产出按 dispose/blend 合成后的整个画布
+    pub fn frames(&self) -> Result<Frames<'_>> {
+        let ihdr = self.ihdr()?;
+        let (animation, frames) = apng::parse_frames(&ihdr, &self.chunks)?;
+        let palette = self.chunk_by_type("PLTE").map(Chunk::data);
+        let transparency = self.chunk_by_type("tRNS").map(Chunk::data);
+        Frames::new(ihdr, animation, frames, palette, transparency)
+    }
+
+    // 将像素数据编码为全新的 PNG
+    pub fn encode_image(image: &Image, options: EncodeOptions) -> Result<Png> {
+        Ok(Png::from_chunks(image::encode(image, &options)?))
+    }
+
+    // 与另一个 PNG 逐数据块比较，报告新增、删除、移动和修改的数据块
+    pub fn diff(&self, other: &Png) -> Vec<ChunkChange> {
+        diff::diff(&self.chunks, &other.chunks)
+    }
+
+    // 按 PNG 规范检查数据块顺序与类型，返回全部诊断信息
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validate::validate(&self.chunks)
+    }
+
     // 将整个 PNG 文件转换为字节向量
     pub fn as_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::from(self.header);
@@ -64,27 +342,24 @@ impl Png {
         }
         bytes
     }
+
+    // 将整个 PNG 文件流式写入目标
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut png_writer = PngWriter::new(writer)?;
+        for chunk in &self.chunks {
+            png_writer.write_chunk(chunk)?;
+        }
+        png_writer.finish()?;
+        Ok(())
+    }
 }
 
 // 实现从字节切片转换为 Png 实例
 impl TryFrom<&[u8]> for Png {
-    type Error = Error;
+    type Error = PngError;
 
     fn try_from(value: &[u8]) -> Result<Self> {
-        if value.len() < 8 || &value[0..8] != &Self::STANDARD_HEADER {
-            return Err(Error::msg("Invalid PNG header"));
-        }
-        let mut chunks = Vec::new();
-        let mut index = 8;
-        while index < value.len() {
-            let chunk = Chunk::try_from(&value[index..])?;
-            chunks.push(chunk.clone());
-            index += 4 + 4 + chunk.length() as usize + 4;
-        }
-        Ok(Png {
-            header: Self::STANDARD_HEADER,
-            chunks,
-        })
+        Png::from_reader(value)
     }
 }
 
@@ -107,6 +382,8 @@ mod tests {
     use super::*;
     use crate::chunk::Chunk;
     use crate::chunk_type::ChunkType;
+    use crate::ihdr::{ColorType, InterlaceMethod};
+    use crate::text::CompressedText;
     use std::str::FromStr;
 
     #[test]
@@ -129,6 +406,40 @@ mod tests {
         assert_eq!(png.chunks().len(), 1);
     }
 
+    #[test]
+    fn test_insert_chunk() {
+        let first = Chunk::new(ChunkType::from_str("FrSt").unwrap(), vec![1]);
+        let last = Chunk::new(ChunkType::from_str("LASt").unwrap(), vec![2]);
+        let middle = Chunk::new(ChunkType::from_str("miDl").unwrap(), vec![3]);
+        let mut png = Png::from_chunks(vec![first, last]);
+        png.insert_chunk(1, middle.clone()).unwrap();
+        assert_eq!(png.chunks()[1], middle);
+        assert!(matches!(
+            png.insert_chunk(4, middle),
+            Err(PngError::IndexOutOfRange { index: 4, len: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_insert_before_and_after() {
+        let idat = ChunkType::from_str("IDAT").unwrap();
+        let iend = ChunkType::from_str("IEND").unwrap();
+        let mut png = Png::from_chunks(vec![
+            Chunk::new(idat, vec![1]),
+            Chunk::new(idat, vec![2]),
+            Chunk::new(iend, vec![]),
+        ]);
+        let message = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hi".to_vec());
+        png.insert_before("IEND", message.clone()).unwrap();
+        assert_eq!(png.chunks()[2], message);
+        assert_eq!(*png.chunks()[3].chunk_type(), iend);
+
+        let note = Chunk::new(ChunkType::from_str("teSt").unwrap(), vec![]);
+        png.insert_after("IDAT", note.clone()).unwrap();
+        assert_eq!(png.chunks()[2], note);
+        assert!(matches!(png.insert_before("tIME", note), Err(PngError::ChunkNotFound(_))));
+    }
+
     #[test]
     fn test_remove_first_chunk() {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
@@ -140,6 +451,21 @@ mod tests {
         assert_eq!(png.chunks().len(), 0);
     }
 
+    #[test]
+    fn test_retain() {
+        let mut png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("tEXt").unwrap(), vec![1]),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("tIME").unwrap(), vec![2]),
+        ]);
+        let removed = png.retain(|c| c.chunk_type().is_critical());
+        let types: Vec<String> = png.chunks().iter().map(|c| c.chunk_type().to_string()).collect();
+        assert_eq!(types, vec!["IHDR", "IDAT"]);
+        assert_eq!(removed.len(), 2);
+        assert_eq!(removed[1].data(), &[2]);
+    }
+
     #[test]
     fn test_header() {
         let png = Png::from_chunks(vec![]);
@@ -156,6 +482,15 @@ mod tests {
         assert_eq!(png.chunks().len(), 1);
     }
 
+    #[test]
+    fn test_chunk_offsets() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), vec![0; 20]),
+            Chunk::new(ChunkType::from_str("LASt").unwrap(), vec![]),
+        ]);
+        assert_eq!(png.chunk_offsets(), vec![8, 40]);
+    }
+
     #[test]
     fn test_chunk_by_type() {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
@@ -167,6 +502,100 @@ mod tests {
         assert_eq!(*found_chunk.chunk_type(), chunk_type);
     }
 
+    #[test]
+    fn test_png_read_payload() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let data: Vec<u8> = (0..100).collect();
+        let mut chunks = vec![Chunk::new(ChunkType::from_str("FrSt").unwrap(), vec![])];
+        chunks.extend(crate::payload::split_payload(chunk_type, &data, 30).unwrap());
+        let png = Png::from_chunks(chunks);
+        assert_eq!(png.chunks_by_type("ruSt").len(), 4);
+        assert_eq!(png.read_payload("ruSt").unwrap(), data);
+        assert!(png.read_payload("LASt").is_err());
+    }
+
+    #[test]
+    fn test_png_attachments() {
+        let first = Attachment::new("a.txt", "text/plain", b"first".to_vec()).unwrap();
+        let second = Attachment::new("b.bin", "application/octet-stream", vec![0, 1, 2]).unwrap();
+        let png = Png::from_chunks(vec![
+            first.to_chunk().unwrap(),
+            Chunk::new(ChunkType::from_str("RuSt").unwrap(), vec![]),
+            second.to_chunk().unwrap(),
+        ]);
+        assert_eq!(png.attachments().unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn test_png_ihdr() {
+        let ihdr = Ihdr::new(3, 2, 8, ColorType::Rgb, InterlaceMethod::None).unwrap();
+        let png = Png::from_chunks(vec![ihdr.to_chunk()]);
+        assert_eq!(png.ihdr().unwrap(), ihdr);
+    }
+
+    #[test]
+    fn test_png_missing_ihdr() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, vec![82, 117, 115, 116]);
+        let png = Png::from_chunks(vec![chunk]);
+        assert!(png.ihdr().is_err());
+    }
+
+    #[test]
+    fn test_png_encode_image_round_trip() {
+        let image = Image {
+            width: 3,
+            height: 2,
+            color_type: ColorType::Grayscale,
+            bit_depth: 8,
+            pixels: vec![0, 128, 255, 255, 128, 0],
+        };
+        let png = Png::encode_image(&image, EncodeOptions::default()).unwrap();
+        let parsed = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        assert_eq!(parsed.decode_image().unwrap(), image);
+    }
+
+    #[test]
+    fn test_png_progressive_passes() {
+        let image = Image {
+            width: 8,
+            height: 8,
+            color_type: ColorType::Grayscale,
+            bit_depth: 8,
+            pixels: (0..64).collect(),
+        };
+        let options = EncodeOptions {
+            interlace_method: InterlaceMethod::Adam7,
+            ..EncodeOptions::default()
+        };
+        let png = Png::encode_image(&image, options).unwrap();
+        let mut passes = png.progressive_passes().unwrap();
+        let mut canvas = passes.blank_image();
+        let mut count = 0;
+        for pass in passes.by_ref() {
+            pass.unwrap().scatter_into(&mut canvas);
+            count += 1;
+        }
+        assert_eq!(count, 7);
+        assert_eq!(canvas, image);
+        assert_eq!(png.decode_image().unwrap(), image);
+    }
+
+    #[test]
+    fn test_progressive_passes_short_data() {
+        // IHDR 声明巨大的尺寸而 IDAT 只有几个字节时，在分配整幅图像之前报错
+        let ihdr = Ihdr::new(100_000, 100_000, 8, ColorType::Rgba, InterlaceMethod::Adam7).unwrap();
+        let png = Png::from_chunks(vec![
+            ihdr.to_chunk(),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), image::deflate(&[0; 16], 6).unwrap()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        match png.progressive_passes() {
+            Err(PngError::InvalidImage(message)) => assert!(message.contains("too short")),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
     #[test]
     fn test_png_as_bytes() {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
@@ -178,6 +607,17 @@ mod tests {
         assert!(bytes.len() > 8);
     }
 
+    #[test]
+    fn test_png_write_to() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = vec![82, 117, 115, 116];
+        let chunk = Chunk::new(chunk_type, data);
+        let png = Png::from_chunks(vec![chunk]);
+        let mut bytes = Vec::new();
+        png.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes, png.as_bytes());
+    }
+
     #[test]
     fn test_png_from_bytes() {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
@@ -200,4 +640,110 @@ mod tests {
         let png_string = format!("{}", png);
         assert!(png_string.contains("PNG File:"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_with_recovers_chunks() {
+        let chunks = vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), b"I am the first chunk".to_vec()),
+            Chunk::new(ChunkType::from_str("LASt").unwrap(), b"I am the last chunk".to_vec()),
+        ];
+        let mut bytes = Png::from_chunks(chunks).as_bytes();
+        let crc_index = 8 + 8 + "I am the first chunk".len();
+        bytes[crc_index] ^= 0xFF;
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+
+        let (png, report) = Png::parse_with(bytes.as_slice(), ParseOptions::lenient()).unwrap();
+        assert_eq!(png.chunks().len(), 2);
+        assert_eq!(png.as_bytes(), bytes);
+        assert_eq!(report.issues.len(), 1);
+        assert!(Png::parse_with(bytes.as_slice(), ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_set_text_replaces_entries() {
+        let mut png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Title\0Old".to_vec()),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![1]),
+            CompressedText::new("Title", "Older").unwrap().to_chunk().unwrap(),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        png.set_text("Title", "新标题").unwrap();
+        png.set_text("Author", "Ferris").unwrap();
+        let entries: Vec<TextEntry> = png.text_entries().into_iter().collect::<Result<_>>().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].keyword(), entries[0].text()), ("Title", "新标题"));
+        assert_eq!(png.chunks()[1].chunk_type().to_string(), "iTXt");
+        assert_eq!((entries[1].keyword(), entries[1].text()), ("Author", "Ferris"));
+        assert_eq!(png.chunks()[3].chunk_type().to_string(), "tEXt");
+        assert_eq!(png.chunks()[4].chunk_type().to_string(), "IEND");
+        assert!(png.set_text("", "empty keyword").is_err());
+    }
+
+    #[test]
+    fn test_text_entries_with_corrupt_chunk() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("zTXt").unwrap(), b"Comment\0\0not zlib".to_vec()),
+            Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Title\0Valid".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        let entries = png.text_entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_err());
+        let entry = entries[1].as_ref().unwrap();
+        assert_eq!((entry.keyword(), entry.text()), ("Title", "Valid"));
+    }
+
+    #[test]
+    fn test_typed_chunk_access() {
+        let mut png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        assert!(matches!(png.decode_chunk::<Attachment>(), Err(PngError::ChunkNotFound(_))));
+        let first = Attachment::new("a.txt", "text/plain", b"first".to_vec()).unwrap();
+        let second = Attachment::new("b.txt", "text/plain", b"second".to_vec()).unwrap();
+        png.put_chunk(&first).unwrap();
+        assert_eq!(png.chunks()[1].chunk_type().to_string(), "atCh");
+        png.put_chunk(&second).unwrap();
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(png.decode_chunk::<Attachment>().unwrap(), second);
+        // 已有多个同类型数据块时替换第一个并删除其余的
+        png.insert_chunk(2, first.to_chunk().unwrap()).unwrap();
+        png.put_chunk(&second).unwrap();
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(png.decode_chunks::<Attachment>().unwrap(), vec![second]);
+
+        let mut registry = ChunkRegistry::standard();
+        registry.register::<Attachment>();
+        let descriptions = png.describe_chunks(&registry);
+        assert_eq!(descriptions[1], "atCh: b.txt (text/plain, 6 bytes, crc 0xb61f1169)");
+        assert!(descriptions[0].starts_with("Chunk {"));
+    }
+
+    #[test]
+    fn test_decode_rgba8_with_palette() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            color_type: ColorType::Indexed,
+            bit_depth: 8,
+            pixels: vec![1, 0],
+        };
+        let options = EncodeOptions {
+            palette: Some(vec![255, 0, 0, 0, 0, 255]),
+            ..EncodeOptions::default()
+        };
+        let mut png = Png::encode_image(&image, options).unwrap();
+        assert_eq!(png.palette().unwrap().entries, [[255, 0, 0], [0, 0, 255]]);
+        assert!(matches!(png.transparency(), Err(PngError::ChunkNotFound(_))));
+        png.insert_after("PLTE", Transparency::Alpha(vec![64]).to_chunk()).unwrap();
+        assert_eq!(png.decode_rgba8().unwrap(), [0, 0, 255, 255, 255, 0, 0, 64]);
+
+        png.insert_after("PLTE", Transparency::Alpha(vec![0; 3]).to_chunk()).unwrap();
+        assert!(matches!(png.transparency(), Err(PngError::InvalidPalette(_))));
+        png.remove_first_chunk("PLTE").unwrap();
+        assert!(png.decode_rgba8().is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+use crate::ihdr::Ihdr;
+use crate::png::Png;
+
+// 借用整个输入缓冲区（例如内存映射的文件）的 PNG 视图
+// 只为数据块索引分配内存，数据块数据始终指向输入缓冲区
+#[derive(Debug, Clone)]
+pub struct PngRef<'a> {
+    chunks: Vec<ChunkRef<'a>>,
+    offsets: Vec<u64>,
+}
+
+impl<'a> PngRef<'a> {
+    // 返回所有数据块视图的切片
+    pub fn chunks(&self) -> &[ChunkRef<'a>] {
+        &self.chunks
+    }
+
+    // 返回每个数据块在输入中的起始字节偏移，与 chunks 一一对应
+    pub fn offsets(&self) -> &[u64] {
+        &self.offsets
+    }
+
+    // 根据数据块类型查找数据块
+    pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&ChunkRef<'a>> {
+        let chunk_type = ChunkType::from_str(chunk_type).ok()?;
+        self.chunks.iter().find(|c| *c.chunk_type() == chunk_type)
+    }
+
+    // 返回所有指定类型的数据块，类型无效时返回空列表
+    pub fn chunks_by_type(&self, chunk_type: &str) -> Vec<&ChunkRef<'a>> {
+        match ChunkType::from_str(chunk_type) {
+            Ok(ct) => self.chunks.iter().filter(|c| *c.chunk_type() == ct).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    // 解析第一个数据块中的图像头信息
+    pub fn ihdr(&self) -> Result<Ihdr> {
+        match self.chunks.first() {
+            Some(chunk) => Ihdr::try_from(chunk),
+            None => Err(PngError::ChunkNotFound(Ihdr::chunk_type())),
+        }
+    }
+
+    // 复制全部数据，转换为拥有所有权的 Png
+    pub fn to_png(&self) -> Png {
+        Png::from_chunks(self.chunks.iter().map(ChunkRef::to_chunk).collect())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PngRef<'a> {
+    type Error = PngError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self> {
+        if value.len() < Png::STANDARD_HEADER.len() || value[..Png::STANDARD_HEADER.len()] != Png::STANDARD_HEADER {
+            return Err(PngError::InvalidSignature);
+        }
+        let mut chunks = Vec::new();
+        let mut offsets = Vec::new();
+        let mut position = Png::STANDARD_HEADER.len();
+        while position < value.len() {
+            let chunk = ChunkRef::parse(&value[position..], position as u64)?;
+            chunks.push(chunk);
+            offsets.push(position as u64);
+            position += chunk.total_length();
+        }
+        Ok(PngRef { chunks, offsets })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+
+    fn testing_png() -> Png {
+        Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), b"I am the first chunk".to_vec()),
+            Chunk::new(ChunkType::from_str("miDl").unwrap(), b"I am another chunk".to_vec()),
+            Chunk::new(ChunkType::from_str("LASt").unwrap(), b"I am the last chunk".to_vec()),
+        ])
+    }
+
+    #[test]
+    fn test_png_ref_indexes_chunks() {
+        let bytes = testing_png().as_bytes();
+        let png = PngRef::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(png.offsets(), &[8, 40, 70]);
+        let middle = png.chunk_by_type("miDl").unwrap();
+        assert_eq!(middle.data().as_ptr(), bytes[48..].as_ptr());
+        assert_eq!(png.chunks_by_type("LASt").len(), 1);
+        assert!(png.chunk_by_type("nope").is_none());
+        assert!(png.ihdr().is_err());
+    }
+
+    #[test]
+    fn test_png_ref_to_png() {
+        let owned = testing_png();
+        let bytes = owned.as_bytes();
+        let png = PngRef::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(png.to_png().as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_png_ref_errors() {
+        let mut bytes = testing_png().as_bytes();
+        assert!(matches!(
+            PngRef::try_from(&bytes[..bytes.len() - 1]),
+            Err(PngError::Truncated { needed: 1, .. })
+        ));
+        bytes[45] ^= 1;
+        assert!(matches!(PngRef::try_from(bytes.as_slice()), Err(PngError::CrcMismatch { offset: 40, .. })));
+        bytes[0] = 0;
+        assert!(matches!(PngRef::try_from(bytes.as_slice()), Err(PngError::InvalidSignature)));
+    }
+}
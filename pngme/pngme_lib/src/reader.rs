@@ -0,0 +1,395 @@
+use std::io::{self, Read};
+
+use crate::chunk::{self, Chunk};
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+use crate::parse::{ErrorAction, ParseIssue, ParseOptions, ParseReport};
+use crate::png::Png;
+
+// 流式 PNG 读取器：校验文件头后逐个产出数据块，无需把整个文件读入内存
+pub struct PngReader<R: Read> {
+    reader: R,
+    offset: u64,
+    finished: bool,
+    chunks_read: usize,
+    chunk_start: u64,
+    last_offset: Option<u64>,
+    options: ParseOptions,
+    issues: Vec<ParseIssue>,
+    // 跳过类型无效的数据块后预读的下一个数据块头部
+    lookahead: Vec<u8>,
+}
+
+impl<R: Read> PngReader<R> {
+    // 创建读取器并校验 PNG 文件头
+    pub fn new(reader: R) -> Result<PngReader<R>> {
+        PngReader::with_options(reader, ParseOptions::default())
+    }
+
+    // 创建按指定选项处理损坏数据块的读取器，文件头无效时仍然返回错误
+    pub fn with_options(mut reader: R, options: ParseOptions) -> Result<PngReader<R>> {
+        let mut header = [0u8; 8];
+        if read_fully(&mut reader, &mut header)? < header.len() || header != Png::STANDARD_HEADER {
+            return Err(PngError::InvalidSignature);
+        }
+        Ok(PngReader {
+            reader,
+            offset: header.len() as u64,
+            finished: false,
+            chunks_read: 0,
+            chunk_start: 0,
+            last_offset: None,
+            options,
+            issues: Vec::new(),
+            lookahead: Vec::new(),
+        })
+    }
+
+    // 返回下一个待读取数据块在文件中的字节偏移
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    // 返回最近产出的数据块在文件中的起始字节偏移
+    pub fn last_offset(&self) -> Option<u64> {
+        self.last_offset
+    }
+
+    // 返回目前为止记录的问题
+    pub fn issues(&self) -> &[ParseIssue] {
+        &self.issues
+    }
+
+    // 结束读取，返回记录的问题和已读取的字节数
+    pub fn into_report(self) -> ParseReport {
+        ParseReport {
+            issues: self.issues,
+            bytes_read: self.offset,
+        }
+    }
+
+    // 取回底层的读取源，已预读的数据块头部不会退回
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // 按处理方式返回错误，或记录问题后继续；chunk_index 为仍被保留的数据块下标
+    fn recover(&mut self, action: ErrorAction, offset: u64, chunk_index: Option<usize>, error: PngError) -> Result<()> {
+        match action {
+            ErrorAction::Error => Err(error),
+            ErrorAction::Warn => {
+                self.issues.push(ParseIssue {
+                    offset,
+                    chunk_index,
+                    error,
+                });
+                Ok(())
+            }
+            ErrorAction::Ignore => Ok(()),
+        }
+    }
+
+    // 读取下一个数据块，到达文件末尾时返回 None
+    // 截断时保留已读到的数据，类型无效的数据块被跳过
+    // 类型无效时长度字段同样不可信：只有跳过后紧接着输入结尾或类型有效的数据块头部时才继续，否则停止解析
+    fn read_chunk(&mut self) -> Result<Option<Chunk>> {
+        loop {
+            let start = self.offset;
+            self.chunk_start = start;
+            // 数据块在 start 之后已读取 consumed 字节、总共需要 total 字节时的截断错误
+            let truncated = |consumed: u64, total: u64| PngError::Truncated {
+                offset: start + consumed,
+                needed: total - consumed,
+            };
+
+            // 长度(4) + 类型(4)
+            let mut header = [0u8; 8];
+            let lookahead = std::mem::take(&mut self.lookahead);
+            let read = read_fully(&mut lookahead.as_slice().chain(&mut self.reader), &mut header)?;
+            if read == 0 {
+                return Ok(None);
+            }
+            if read < 4 {
+                self.recover(self.options.on_truncation, start, None, truncated(read as u64, 12))?;
+                self.offset += read as u64;
+                return Ok(None);
+            }
+            let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+            let total = 12 + length as u64;
+            if read < header.len() {
+                self.recover(self.options.on_truncation, start, None, truncated(read as u64, total))?;
+                self.offset += read as u64;
+                return Ok(None);
+            }
+            let chunk_type = match ChunkType::try_from([header[4], header[5], header[6], header[7]]) {
+                Ok(chunk_type) => Some(chunk_type),
+                Err(e) => {
+                    self.recover(self.options.on_invalid_chunk, start, None, e)?;
+                    None
+                }
+            };
+
+            // 截断时仍保留的数据块在结果中的下标
+            let kept = chunk_type.map(|_| self.chunks_read);
+
+            // 通过 take 限制读取长度，避免被伪造的长度字段诱导分配超大缓冲区；跳过的数据块不保留数据
+            let mut data = Vec::new();
+            let mut limited = self.reader.by_ref().take(length as u64);
+            let read = match chunk_type {
+                Some(_) => limited.read_to_end(&mut data)? as u64,
+                None => io::copy(&mut limited, &mut io::sink())?,
+            };
+            if read < length as u64 {
+                self.recover(self.options.on_truncation, start, kept, truncated(8 + read, total))?;
+                self.offset += 8 + read;
+                return Ok(chunk_type.map(|chunk_type| Chunk::new(chunk_type, data)));
+            }
+
+            let mut crc_bytes = [0u8; 4];
+            let read = read_fully(&mut self.reader, &mut crc_bytes)?;
+            if read < crc_bytes.len() {
+                let error = truncated(8 + length as u64 + read as u64, total);
+                self.recover(self.options.on_truncation, start, kept, error)?;
+                self.offset += 8 + length as u64 + read as u64;
+                return Ok(chunk_type.map(|chunk_type| Chunk::new(chunk_type, data)));
+            }
+            self.offset += total;
+
+            let chunk_type = match chunk_type {
+                Some(chunk_type) => chunk_type,
+                None => {
+                    let mut next = [0u8; 8];
+                    let read = read_fully(&mut self.reader, &mut next)?;
+                    if read == next.len() {
+                        if let Err(e) = ChunkType::try_from([next[4], next[5], next[6], next[7]]) {
+                            self.recover(self.options.on_invalid_chunk, self.offset, None, e)?;
+                            return Ok(None);
+                        }
+                    }
+                    self.lookahead = next[..read].to_vec();
+                    continue;
+                }
+            };
+            let expected = u32::from_be_bytes(crc_bytes);
+            let actual = chunk::checksum(&chunk_type, &data);
+            if actual != expected {
+                let error = PngError::CrcMismatch {
+                    chunk_type,
+                    offset: start,
+                    expected,
+                    actual,
+                };
+                self.recover(self.options.on_crc_error, start, Some(self.chunks_read), error)?;
+            }
+            return Ok(Some(Chunk::from_raw(chunk_type, data, expected)));
+        }
+    }
+}
+
+impl<R: Read> Iterator for PngReader<R> {
+    type Item = Result<Chunk>;
+
+    // 出错或读到末尾后不再产出任何数据块
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_chunk() {
+            Ok(Some(chunk)) => {
+                self.chunks_read += 1;
+                self.last_offset = Some(self.chunk_start);
+                Some(Ok(chunk))
+            }
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// 尽可能填满缓冲区，返回实际读取的字节数（遇到文件末尾时可能小于缓冲区长度）
+fn read_fully<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn testing_bytes() -> Vec<u8> {
+        let chunks = vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), b"I am the first chunk".to_vec()),
+            Chunk::new(ChunkType::from_str("miDl").unwrap(), b"I am another chunk".to_vec()),
+            Chunk::new(ChunkType::from_str("LASt").unwrap(), b"I am the last chunk".to_vec()),
+        ];
+        Png::from_chunks(chunks).as_bytes()
+    }
+
+    #[test]
+    fn test_reader_yields_chunks_in_order() {
+        let bytes = testing_bytes();
+        let reader = PngReader::new(bytes.as_slice()).unwrap();
+        let chunks: Vec<Chunk> = reader.collect::<Result<_>>().unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].chunk_type().to_string(), "FrSt");
+        assert_eq!(chunks[2].data_as_string().unwrap(), "I am the last chunk");
+    }
+
+    #[test]
+    fn test_reader_tracks_offset() {
+        let bytes = testing_bytes();
+        let mut reader = PngReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.offset(), 8);
+        assert_eq!(reader.last_offset(), None);
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(reader.offset(), 8 + 12 + first.length() as u64);
+        assert_eq!(reader.last_offset(), Some(8));
+    }
+
+    #[test]
+    fn test_reader_invalid_header() {
+        let mut bytes = testing_bytes();
+        bytes[0] = 0;
+        assert!(matches!(PngReader::new(bytes.as_slice()), Err(PngError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_reader_truncated_chunk() {
+        let bytes = testing_bytes();
+        let truncated = &bytes[..bytes.len() - 2];
+        let results: Vec<Result<Chunk>> = PngReader::new(truncated).unwrap().collect();
+        assert_eq!(results.len(), 3);
+        match &results[2] {
+            Err(PngError::Truncated { offset, needed }) => {
+                assert_eq!(*offset, bytes.len() as u64 - 2);
+                assert_eq!(*needed, 2);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reader_stops_after_error() {
+        let mut bytes = testing_bytes();
+        // 破坏第一个数据块的 CRC
+        let crc_index = 8 + 8 + "I am the first chunk".len();
+        bytes[crc_index] ^= 0xFF;
+        let mut reader = PngReader::new(bytes.as_slice()).unwrap();
+        match reader.next().unwrap() {
+            Err(PngError::CrcMismatch { chunk_type, offset, .. }) => {
+                assert_eq!(chunk_type.to_string(), "FrSt");
+                assert_eq!(offset, 8);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_lenient_keeps_chunk_with_bad_crc() {
+        let mut bytes = testing_bytes();
+        let crc_index = 8 + 8 + "I am the first chunk".len();
+        bytes[crc_index] ^= 0xFF;
+        let mut reader = PngReader::with_options(bytes.as_slice(), ParseOptions::lenient()).unwrap();
+        let chunks: Vec<Chunk> = reader.by_ref().collect::<Result<_>>().unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert!(!chunks[0].has_valid_crc());
+        assert!(chunks[1].has_valid_crc());
+        let report = reader.into_report();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].offset, 8);
+        assert_eq!(report.issues[0].chunk_index, Some(0));
+        assert!(matches!(report.issues[0].error, PngError::CrcMismatch { .. }));
+        assert_eq!(report.bytes_read, bytes.len() as u64);
+    }
+
+    #[test]
+    fn test_lenient_keeps_truncated_data() {
+        let bytes = testing_bytes();
+        let truncated = &bytes[..bytes.len() - 10];
+        let mut reader = PngReader::with_options(truncated, ParseOptions::lenient()).unwrap();
+        let chunks: Vec<Chunk> = reader.by_ref().collect::<Result<_>>().unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].data_as_string().unwrap(), "I am the last");
+        let report = reader.into_report();
+        assert_eq!(report.issues.len(), 1);
+        assert!(matches!(report.issues[0].error, PngError::Truncated { needed: 10, .. }));
+        assert_eq!(report.issues[0].chunk_index, Some(2));
+        assert_eq!(report.bytes_read, truncated.len() as u64);
+    }
+
+    #[test]
+    fn test_lenient_skips_invalid_chunk_type() {
+        let mut bytes = testing_bytes();
+        // 把第二个数据块的类型改为非字母
+        let type_index = 8 + 12 + "I am the first chunk".len() + 4;
+        bytes[type_index] = b'1';
+        let mut reader = PngReader::with_options(bytes.as_slice(), ParseOptions::lenient()).unwrap();
+        let chunks: Vec<Chunk> = reader.by_ref().collect::<Result<_>>().unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].chunk_type().to_string(), "LASt");
+        assert!(matches!(reader.issues()[0].error, PngError::InvalidChunkType(_)));
+        assert_eq!(reader.issues()[0].chunk_index, None);
+        assert_eq!(reader.last_offset(), Some(8 + 12 + 20 + 12 + 18));
+    }
+
+    #[test]
+    fn test_lenient_stops_when_skip_lands_inside_data() {
+        let mut bytes = testing_bytes();
+        // 第二个数据块类型无效且长度被改为 2，跳过后落在数据中间，下一个"类型"为 "er c"
+        let length_index = 8 + 12 + "I am the first chunk".len();
+        bytes[length_index..length_index + 4].copy_from_slice(&2u32.to_be_bytes());
+        bytes[length_index + 4] = b'1';
+        let mut reader = PngReader::with_options(bytes.as_slice(), ParseOptions::lenient()).unwrap();
+        let chunks: Vec<Chunk> = reader.by_ref().collect::<Result<_>>().unwrap();
+        assert_eq!(chunks.len(), 1);
+        let report = reader.into_report();
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(report.issues[1].offset, length_index as u64 + 14);
+        assert!(matches!(report.issues[1].error, PngError::InvalidChunkType(_)));
+        assert_eq!(report.bytes_read, length_index as u64 + 14);
+    }
+
+    #[test]
+    fn test_lenient_skip_bounded_by_input() {
+        let mut bytes = testing_bytes();
+        // 类型无效的数据块声明了超出输入的长度，只读到输入结尾并报告截断
+        let length_index = 8 + 12 + "I am the first chunk".len();
+        bytes[length_index..length_index + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        bytes[length_index + 4] = b'1';
+        let mut reader = PngReader::with_options(bytes.as_slice(), ParseOptions::lenient()).unwrap();
+        assert_eq!(reader.by_ref().count(), 1);
+        let report = reader.into_report();
+        assert_eq!(report.issues.len(), 2);
+        assert!(matches!(report.issues[1].error, PngError::Truncated { .. }));
+        assert_eq!(report.bytes_read, bytes.len() as u64);
+    }
+
+    #[test]
+    fn test_ignore_records_nothing() {
+        let bytes = testing_bytes();
+        let options = ParseOptions {
+            on_truncation: ErrorAction::Ignore,
+            ..ParseOptions::default()
+        };
+        let mut reader = PngReader::with_options(&bytes[..bytes.len() - 2], options).unwrap();
+        assert_eq!(reader.by_ref().count(), 3);
+        assert!(reader.into_report().is_clean());
+    }
+}
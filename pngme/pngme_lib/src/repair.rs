@@ -0,0 +1,154 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::error::PngError;
+use crate::parse::ParseReport;
+
+// 修复过程中做出的一项改动
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    // 按数据重新计算了 CRC
+    RecomputedCrc { index: usize, chunk_type: ChunkType, stored: u32, computed: u32 },
+    // 长度超出文件末尾的数据块被缩短为实际可读到的数据，missing 为缺少的字节数
+    TruncatedChunk { index: usize, chunk_type: ChunkType, offset: u64, missing: u64 },
+    // 丢弃了从 offset 开始无法识别的数据
+    DroppedData { offset: u64, reason: String },
+    // 在末尾追加了缺失的 IEND
+    AppendedIend,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repair::RecomputedCrc { index, chunk_type, stored, computed } => write!(
+                f,
+                "chunk {} ({}): replaced CRC {:#010x} with {:#010x}",
+                index, chunk_type, stored, computed
+            ),
+            Repair::TruncatedChunk { index, chunk_type, offset, missing } => write!(
+                f,
+                "chunk {} ({}) at offset {}: shortened to the available data ({} bytes missing)",
+                index, chunk_type, offset, missing
+            ),
+            Repair::DroppedData { offset, reason } => write!(f, "dropped data at offset {}: {}", offset, reason),
+            Repair::AppendedIend => write!(f, "appended missing IEND chunk"),
+        }
+    }
+}
+
+// 根据宽松解析的报告修复恢复出的数据块，返回所做的全部改动
+pub(crate) fn repair(chunks: &mut Vec<Chunk>, report: &ParseReport) -> Vec<Repair> {
+    let mut repairs = Vec::new();
+    for issue in &report.issues {
+        match (&issue.error, issue.chunk_index) {
+            // 截断的数据块在解析时已按实际数据重建，这里只记录改动
+            (PngError::Truncated { needed, .. }, Some(index)) => repairs.push(Repair::TruncatedChunk {
+                index,
+                chunk_type: *chunks[index].chunk_type(),
+                offset: issue.offset,
+                missing: *needed,
+            }),
+            (PngError::CrcMismatch { .. }, Some(_)) => {}
+            (error, _) => repairs.push(Repair::DroppedData {
+                offset: issue.offset,
+                reason: error.to_string(),
+            }),
+        }
+    }
+
+    for (index, chunk) in chunks.iter_mut().enumerate() {
+        if !chunk.has_valid_crc() {
+            let fixed = Chunk::new(*chunk.chunk_type(), chunk.data().to_vec());
+            repairs.push(Repair::RecomputedCrc {
+                index,
+                chunk_type: *chunk.chunk_type(),
+                stored: chunk.crc(),
+                computed: fixed.crc(),
+            });
+            *chunk = fixed;
+        }
+    }
+
+    let iend = ChunkType::from_str("IEND").unwrap();
+    if !chunks.iter().any(|c| *c.chunk_type() == iend) {
+        chunks.push(Chunk::new(iend, Vec::new()));
+        repairs.push(Repair::AppendedIend);
+    }
+    repairs
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::png::Png;
+
+    fn testing_bytes() -> Vec<u8> {
+        let chunks = vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), b"some image data".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ];
+        Png::from_chunks(chunks).as_bytes()
+    }
+
+    fn repaired(bytes: &[u8]) -> (Png, Vec<Repair>) {
+        Png::repair(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_repair_clean_file() {
+        let bytes = testing_bytes();
+        let (png, repairs) = repaired(&bytes);
+        assert!(repairs.is_empty());
+        assert_eq!(png.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_repair_recomputes_crc() {
+        let mut bytes = testing_bytes();
+        // 破坏 IDAT 的 CRC
+        let crc_index = 8 + 25 + 8 + "some image data".len();
+        bytes[crc_index] ^= 0xFF;
+        let (png, repairs) = repaired(&bytes);
+        assert_eq!(repairs.len(), 1);
+        assert!(matches!(repairs[0], Repair::RecomputedCrc { index: 1, .. }));
+        assert_eq!(png.as_bytes(), testing_bytes());
+    }
+
+    #[test]
+    fn test_repair_truncated_file() {
+        let bytes = testing_bytes();
+        // 去掉 IEND 以及 IDAT 的最后 4 字节数据和 CRC
+        let truncated = &bytes[..bytes.len() - 12 - 8];
+        let (png, repairs) = repaired(truncated);
+        assert_eq!(
+            repairs,
+            vec![
+                Repair::TruncatedChunk {
+                    index: 1,
+                    chunk_type: ChunkType::from_str("IDAT").unwrap(),
+                    offset: 33,
+                    missing: 8,
+                },
+                Repair::AppendedIend,
+            ]
+        );
+        assert_eq!(png.chunks()[1].data(), b"some image ");
+        assert!(png.chunks().iter().all(|c| c.has_valid_crc()));
+        assert!(Png::try_from(png.as_bytes().as_slice()).is_ok());
+    }
+
+    #[test]
+    fn test_repair_drops_garbage() {
+        let mut bytes = testing_bytes();
+        bytes.extend_from_slice(&[0, 0, 0, 2, b'#', b'!', b'#', b'#', 1, 2, 9, 9, 9, 9, 0xFF, 0xFF, 0xFF]);
+        let (png, repairs) = repaired(&bytes);
+        assert_eq!(repairs.len(), 2);
+        assert!(matches!(repairs[0], Repair::DroppedData { .. }));
+        assert!(matches!(repairs[1], Repair::DroppedData { .. }));
+        assert_eq!(png.as_bytes(), testing_bytes());
+    }
+}
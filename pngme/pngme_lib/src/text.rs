@@ -0,0 +1,461 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::error::{PngError, Result};
+
+// 关键字的最大字节数
+const MAX_KEYWORD_LENGTH: usize = 79;
+// set_text 自动选择压缩格式的文本长度阈值
+const COMPRESSION_THRESHOLD: usize = 1024;
+// 压缩文本解压后允许的最大字节数，防止压缩炸弹耗尽内存
+const MAX_TEXT_LENGTH: usize = 1 << 24;
+
+// 检查关键字：1-79 个可打印的 Latin-1 字符，首尾不能是空格，也不能有连续空格
+fn check_keyword(keyword: &str) -> Result<()> {
+    let length = keyword.chars().count();
+    if length == 0 || length > MAX_KEYWORD_LENGTH {
+        return Err(PngError::InvalidText(format!("Keyword must be 1-79 characters long, found {}", length)));
+    }
+    if let Some(c) = keyword.chars().find(|&c| !matches!(c as u32, 32..=126 | 161..=255)) {
+        return Err(PngError::InvalidText(format!("Keyword contains invalid character {:?}", c)));
+    }
+    if keyword.starts_with(' ') || keyword.ends_with(' ') || keyword.contains("  ") {
+        return Err(PngError::InvalidText(format!("Keyword {:?} has leading, trailing or consecutive spaces", keyword)));
+    }
+    Ok(())
+}
+
+// 把字符串编码为 Latin-1，遇到无法表示的字符或 NUL 时返回错误
+fn encode_latin1(text: &str) -> Result<Vec<u8>> {
+    text.chars()
+        .map(|c| match c as u32 {
+            1..=255 => Ok(c as u8),
+            _ => Err(PngError::InvalidText(format!("Character {:?} cannot be stored as Latin-1 text", c))),
+        })
+        .collect()
+}
+
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+// 是否能以 Latin-1 存放在 tEXt 或 zTXt 中
+fn is_latin1(text: &str) -> bool {
+    text.chars().all(|c| matches!(c as u32, 1..=255))
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = ZlibDecoder::new(data).take(MAX_TEXT_LENGTH as u64 + 1);
+    let mut text = Vec::new();
+    decoder
+        .read_to_end(&mut text)
+        .map_err(|e| PngError::Compression(format!("Failed to inflate text: {}", e)))?;
+    if text.len() > MAX_TEXT_LENGTH {
+        return Err(PngError::InvalidText(format!("Decompressed text exceeds {} bytes", MAX_TEXT_LENGTH)));
+    }
+    Ok(text)
+}
+
+// 取出以 NUL 结尾的字段，返回字段与剩余数据
+fn split_null<'a>(data: &'a [u8], field: &str) -> Result<(&'a [u8], &'a [u8])> {
+    match data.iter().position(|&b| b == 0) {
+        Some(index) => Ok((&data[..index], &data[index + 1..])),
+        None => Err(PngError::InvalidText(format!("Missing null separator after {}", field))),
+    }
+}
+
+// 检查数据块类型并解析以 NUL 结尾的关键字
+fn read_keyword<'a>(chunk: &'a Chunk, expected: &str) -> Result<(String, &'a [u8])> {
+    if chunk.chunk_type().to_string() != expected {
+        return Err(PngError::InvalidText(format!("Expected {} chunk, found {}", expected, chunk.chunk_type())));
+    }
+    let (keyword, rest) = split_null(chunk.data(), "keyword")?;
+    let keyword = decode_latin1(keyword);
+    check_keyword(&keyword)?;
+    Ok((keyword, rest))
+}
+
+// 只支持 zlib 一种压缩方法
+fn check_compression_method(method: u8) -> Result<()> {
+    if method != 0 {
+        return Err(PngError::InvalidText(format!("Unsupported compression method: {}", method)));
+    }
+    Ok(())
+}
+
+// tEXt 数据块：未压缩的 Latin-1 文本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Text {
+    pub keyword: String,
+    pub text: String,
+}
+
+impl Text {
+    // 返回 tEXt 的数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("tEXt").unwrap()
+    }
+
+    // 创建并校验关键字和文本
+    pub fn new(keyword: &str, text: &str) -> Result<Text> {
+        check_keyword(keyword)?;
+        encode_latin1(text)?;
+        Ok(Text {
+            keyword: keyword.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    // 编码为 tEXt 数据块
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        check_keyword(&self.keyword)?;
+        let mut data = encode_latin1(&self.keyword)?;
+        data.push(0);
+        data.extend_from_slice(&encode_latin1(&self.text)?);
+        Ok(Chunk::new(Self::chunk_type(), data))
+    }
+}
+
+impl TryFrom<&Chunk> for Text {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        let (keyword, text) = read_keyword(chunk, "tEXt")?;
+        if text.contains(&0) {
+            return Err(PngError::InvalidText("tEXt text must not contain null characters".to_string()));
+        }
+        Ok(Text {
+            keyword,
+            text: decode_latin1(text),
+        })
+    }
+}
+
+// zTXt 数据块：zlib 压缩的 Latin-1 文本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedText {
+    pub keyword: String,
+    pub text: String,
+}
+
+impl CompressedText {
+    // 返回 zTXt 的数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("zTXt").unwrap()
+    }
+
+    // 创建并校验关键字和文本
+    pub fn new(keyword: &str, text: &str) -> Result<CompressedText> {
+        check_keyword(keyword)?;
+        encode_latin1(text)?;
+        Ok(CompressedText {
+            keyword: keyword.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    // 压缩文本并编码为 zTXt 数据块
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        check_keyword(&self.keyword)?;
+        let mut data = encode_latin1(&self.keyword)?;
+        data.push(0);
+        data.push(0);
+        data.extend_from_slice(&compress(&encode_latin1(&self.text)?)?);
+        Ok(Chunk::new(Self::chunk_type(), data))
+    }
+}
+
+impl TryFrom<&Chunk> for CompressedText {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        let (keyword, rest) = read_keyword(chunk, "zTXt")?;
+        let (&method, compressed) = rest
+            .split_first()
+            .ok_or_else(|| PngError::InvalidText("zTXt chunk is missing the compression method".to_string()))?;
+        check_compression_method(method)?;
+        Ok(CompressedText {
+            keyword,
+            text: decode_latin1(&decompress(compressed)?),
+        })
+    }
+}
+
+// iTXt 数据块：UTF-8 文本，可选压缩，附带语言标签和翻译后的关键字
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternationalText {
+    pub keyword: String,
+    pub compressed: bool,
+    // RFC 3066 语言标签，例如 "en-US"；空字符串表示未指定
+    pub language: String,
+    pub translated_keyword: String,
+    pub text: String,
+}
+
+impl InternationalText {
+    // 返回 iTXt 的数据块类型
+    pub fn chunk_type() -> ChunkType {
+        ChunkType::from_str("iTXt").unwrap()
+    }
+
+    // 创建未指定语言的 iTXt 并校验关键字
+    pub fn new(keyword: &str, text: &str, compressed: bool) -> Result<InternationalText> {
+        check_keyword(keyword)?;
+        Ok(InternationalText {
+            keyword: keyword.to_string(),
+            compressed,
+            language: String::new(),
+            translated_keyword: String::new(),
+            text: text.to_string(),
+        })
+    }
+
+    // 设置语言标签和翻译后的关键字，语言标签只能由 ASCII 字母、数字和连字符组成
+    pub fn with_language(mut self, language: &str, translated_keyword: &str) -> Result<InternationalText> {
+        check_language(language)?;
+        self.language = language.to_string();
+        self.translated_keyword = translated_keyword.to_string();
+        Ok(self)
+    }
+
+    // 编码为 iTXt 数据块，compressed 为 true 时压缩文本
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        check_keyword(&self.keyword)?;
+        check_language(&self.language)?;
+        if self.translated_keyword.contains('\0') {
+            return Err(PngError::InvalidText("Translated keyword must not contain null characters".to_string()));
+        }
+        let mut data = encode_latin1(&self.keyword)?;
+        data.push(0);
+        data.push(self.compressed as u8);
+        data.push(0);
+        data.extend_from_slice(self.language.as_bytes());
+        data.push(0);
+        data.extend_from_slice(self.translated_keyword.as_bytes());
+        data.push(0);
+        if self.compressed {
+            data.extend_from_slice(&compress(self.text.as_bytes())?);
+        } else {
+            data.extend_from_slice(self.text.as_bytes());
+        }
+        Ok(Chunk::new(Self::chunk_type(), data))
+    }
+}
+
+fn check_language(language: &str) -> Result<()> {
+    if !language.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return Err(PngError::InvalidText(format!("Invalid language tag {:?}", language)));
+    }
+    Ok(())
+}
+
+impl TryFrom<&Chunk> for InternationalText {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        let (keyword, rest) = read_keyword(chunk, "iTXt")?;
+        if rest.len() < 2 {
+            return Err(PngError::InvalidText("iTXt chunk is missing the compression fields".to_string()));
+        }
+        let compressed = match rest[0] {
+            0 => false,
+            1 => true,
+            flag => return Err(PngError::InvalidText(format!("Invalid compression flag: {}", flag))),
+        };
+        check_compression_method(rest[1])?;
+        let (language, rest) = split_null(&rest[2..], "language tag")?;
+        let (translated_keyword, text) = split_null(rest, "translated keyword")?;
+        let language = String::from_utf8(language.to_vec())?;
+        check_language(&language)?;
+        let text = if compressed { decompress(text)? } else { text.to_vec() };
+        Ok(InternationalText {
+            keyword,
+            compressed,
+            language,
+            translated_keyword: String::from_utf8(translated_keyword.to_vec())?,
+            text: String::from_utf8(text)?,
+        })
+    }
+}
+
+// PNG 中的一条文本元数据，来自 tEXt、zTXt 或 iTXt 数据块
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextEntry {
+    Text(Text),
+    Compressed(CompressedText),
+    International(InternationalText),
+}
+
+impl TextEntry {
+    // 可存放文本元数据的数据块类型
+    pub const CHUNK_TYPES: [&'static str; 3] = ["tEXt", "zTXt", "iTXt"];
+
+    // 按文本内容选择数据块类型：能用 Latin-1 表示时使用 tEXt，否则使用 iTXt；
+    // 文本超过 1024 字节时改用压缩格式
+    pub fn new(keyword: &str, text: &str) -> Result<TextEntry> {
+        let compressed = text.len() > COMPRESSION_THRESHOLD;
+        Ok(match (is_latin1(text), compressed) {
+            (true, false) => TextEntry::Text(Text::new(keyword, text)?),
+            (true, true) => TextEntry::Compressed(CompressedText::new(keyword, text)?),
+            (false, _) => TextEntry::International(InternationalText::new(keyword, text, compressed)?),
+        })
+    }
+
+    // 返回关键字
+    pub fn keyword(&self) -> &str {
+        match self {
+            TextEntry::Text(t) => &t.keyword,
+            TextEntry::Compressed(t) => &t.keyword,
+            TextEntry::International(t) => &t.keyword,
+        }
+    }
+
+    // 返回解压后的文本
+    pub fn text(&self) -> &str {
+        match self {
+            TextEntry::Text(t) => &t.text,
+            TextEntry::Compressed(t) => &t.text,
+            TextEntry::International(t) => &t.text,
+        }
+    }
+
+    // 返回存放该文本的数据块类型
+    pub fn chunk_type(&self) -> ChunkType {
+        match self {
+            TextEntry::Text(_) => Text::chunk_type(),
+            TextEntry::Compressed(_) => CompressedText::chunk_type(),
+            TextEntry::International(_) => InternationalText::chunk_type(),
+        }
+    }
+
+    // 编码为对应类型的数据块
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        match self {
+            TextEntry::Text(t) => t.to_chunk(),
+            TextEntry::Compressed(t) => t.to_chunk(),
+            TextEntry::International(t) => t.to_chunk(),
+        }
+    }
+}
+
+impl TryFrom<&Chunk> for TextEntry {
+    type Error = PngError;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        match chunk.chunk_type().to_string().as_str() {
+            "tEXt" => Ok(TextEntry::Text(Text::try_from(chunk)?)),
+            "zTXt" => Ok(TextEntry::Compressed(CompressedText::try_from(chunk)?)),
+            "iTXt" => Ok(TextEntry::International(InternationalText::try_from(chunk)?)),
+            other => Err(PngError::InvalidText(format!("{} is not a text chunk", other))),
+        }
+    }
+}
+
+// 格式为 "类型 关键字: 文本"，iTXt 附带语言标签和翻译后的关键字
+impl fmt::Display for TextEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.chunk_type(), self.keyword())?;
+        if let TextEntry::International(t) = self {
+            if !t.language.is_empty() {
+                write!(f, " [{}]", t.language)?;
+            }
+            if !t.translated_keyword.is_empty() {
+                write!(f, " ({})", t.translated_keyword)?;
+            }
+        }
+        write!(f, ": {}", self.text())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_rules() {
+        assert!(check_keyword("Title").is_ok());
+        assert!(check_keyword("Création time").is_ok());
+        assert!(check_keyword("").is_err());
+        assert!(check_keyword(&"k".repeat(80)).is_err());
+        assert!(check_keyword(" Title").is_err());
+        assert!(check_keyword("Title ").is_err());
+        assert!(check_keyword("Two  spaces").is_err());
+        assert!(check_keyword("Tab\there").is_err());
+        assert!(check_keyword("标题").is_err());
+    }
+
+    #[test]
+    fn test_text_latin1_round_trip() {
+        let text = Text::new("Author", "Zoë").unwrap();
+        let chunk = text.to_chunk().unwrap();
+        assert_eq!(chunk.data(), b"Author\0Zo\xeb");
+        assert_eq!(Text::try_from(&chunk).unwrap(), text);
+        assert!(Text::new("Author", "Zoë 😀").is_err());
+    }
+
+    #[test]
+    fn test_compressed_text_round_trip() {
+        let text = CompressedText::new("Comment", &"lorem ipsum ".repeat(100)).unwrap();
+        let chunk = text.to_chunk().unwrap();
+        assert!(chunk.data().len() < 200);
+        assert_eq!(&chunk.data()[..9], b"Comment\0\0");
+        assert_eq!(CompressedText::try_from(&chunk).unwrap(), text);
+    }
+
+    #[test]
+    fn test_international_text_round_trip() {
+        for compressed in [false, true] {
+            let text = InternationalText::new("Title", "你好，世界", compressed)
+                .unwrap()
+                .with_language("zh-CN", "标题")
+                .unwrap();
+            let chunk = text.to_chunk().unwrap();
+            assert_eq!(InternationalText::try_from(&chunk).unwrap(), text);
+        }
+        assert!(InternationalText::new("Title", "", false).unwrap().with_language("en US", "").is_err());
+    }
+
+    #[test]
+    fn test_invalid_text_chunks() {
+        let chunk = |chunk_type: &str, data: &[u8]| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec());
+        assert!(Text::try_from(&chunk("tEXt", b"no separator")).is_err());
+        assert!(Text::try_from(&chunk("tEXt", b" bad\0text")).is_err());
+        assert!(CompressedText::try_from(&chunk("zTXt", b"Key\0\x01xx")).is_err());
+        assert!(InternationalText::try_from(&chunk("iTXt", b"Key\0\x02\0\0\0text")).is_err());
+        assert!(TextEntry::try_from(&chunk("IDAT", b"Key\0text")).is_err());
+    }
+
+    #[test]
+    fn test_decompressed_text_limit() {
+        let mut data = b"Comment\0\0".to_vec();
+        data.extend_from_slice(&compress(&vec![b'a'; MAX_TEXT_LENGTH + 1]).unwrap());
+        let chunk = Chunk::new(ChunkType::from_str("zTXt").unwrap(), data);
+        match CompressedText::try_from(&chunk) {
+            Err(PngError::InvalidText(message)) => assert!(message.contains("exceeds")),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_entry_selects_chunk_type() {
+        assert_eq!(TextEntry::new("Title", "plain").unwrap().chunk_type().to_string(), "tEXt");
+        assert_eq!(TextEntry::new("Title", &"x".repeat(2000)).unwrap().chunk_type().to_string(), "zTXt");
+        let entry = TextEntry::new("Title", "héllo ✓").unwrap();
+        assert_eq!(entry.chunk_type().to_string(), "iTXt");
+        assert_eq!(entry.to_string(), "iTXt Title: héllo ✓");
+    }
+}
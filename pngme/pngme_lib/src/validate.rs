@@ -0,0 +1,586 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::ancillary::{Background, KnownChunk};
+use crate::apng::{AnimationControl, FrameControl};
+use crate::chunk::Chunk;
+use crate::ihdr::{ColorType, Ihdr};
+use crate::palette::{Palette, Transparency};
+
+// 诊断信息的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+// 一条规范检查结果
+// chunk_index 和 offset 指向出问题的数据块；问题与具体数据块无关时（如缺少 IEND）为 None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub chunk_index: Option<usize>,
+    pub offset: Option<u64>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.chunk_index, self.offset) {
+            (Some(index), Some(offset)) => write!(f, "{}: chunk {} at offset {}: {}", self.severity, index, offset, self.message),
+            _ => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+// 只能出现一次的辅助数据块
+const UNIQUE_ANCILLARY: [&str; 11] = ["cHRM", "gAMA", "iCCP", "sBIT", "sRGB", "bKGD", "hIST", "tRNS", "pHYs", "tIME", "acTL"];
+// 必须出现在 PLTE 和 IDAT 之前的辅助数据块
+const BEFORE_PLTE: [&str; 5] = ["cHRM", "gAMA", "iCCP", "sBIT", "sRGB"];
+// 必须出现在 PLTE 之后、IDAT 之前的辅助数据块
+const AFTER_PLTE: [&str; 3] = ["bKGD", "hIST", "tRNS"];
+// 必须出现在 IDAT 之前的辅助数据块
+const BEFORE_IDAT: [&str; 3] = ["pHYs", "sPLT", "acTL"];
+// 规范定义的关键数据块
+const KNOWN_CRITICAL: [&str; 4] = ["IHDR", "PLTE", "IDAT", "IEND"];
+
+// 逐个数据块检查时累积的状态
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+    offset: u64,
+    seen: Vec<String>,
+    plte_index: Option<usize>,
+    // 文件中任意位置是否有 PLTE，用于判断 bKGD、hIST、tRNS 是否出现在 PLTE 之前
+    has_plte: bool,
+    // 有效的 IHDR 与 PLTE，用于检查 tRNS、bKGD、sBIT 和 hIST 的内容
+    ihdr: Option<Ihdr>,
+    palette: Option<Palette>,
+    // 出现在 PLTE 之前的索引色 tRNS，透明度项数留到 finish 中与 PLTE 比较
+    pending_transparency: Option<(usize, Transparency)>,
+    first_idat: Option<usize>,
+    last_idat: Option<usize>,
+    iend_index: Option<usize>,
+    // acTL 声明的帧数
+    animation_frames: Option<u32>,
+    // 已出现的 fcTL 数量，以及下一个 fcTL/fdAT 应有的序号
+    frame_count: u32,
+    next_sequence: u32,
+}
+
+impl Validator {
+    fn report(&mut self, severity: Severity, index: usize, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            chunk_index: Some(index),
+            offset: Some(self.offset),
+            message,
+        });
+    }
+
+    fn report_file(&mut self, severity: Severity, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            chunk_index: None,
+            offset: None,
+            message,
+        });
+    }
+
+    fn check_chunk(&mut self, index: usize, chunk: &Chunk) {
+        let chunk_type = chunk.chunk_type();
+        let name = chunk_type.to_string();
+        let critical = chunk_type.is_critical();
+
+        if !chunk_type.is_reserved_bit_valid() {
+            self.report(Severity::Error, index, format!("Chunk type {} has the reserved bit set", name));
+        } else if !chunk_type.is_valid() {
+            self.report(Severity::Error, index, format!("Chunk type {} is not valid", name));
+        }
+        if critical && !KNOWN_CRITICAL.contains(&name.as_str()) {
+            self.report(Severity::Error, index, format!("Unknown critical chunk {}", name));
+        }
+
+        if let Some(iend) = self.iend_index {
+            self.report(Severity::Error, index, format!("{} chunk appears after IEND at chunk {}", name, iend));
+        }
+        if index == 0 && name != "IHDR" {
+            self.report(Severity::Error, index, format!("First chunk must be IHDR, found {}", name));
+        }
+
+        let duplicate = self.seen.contains(&name);
+        let ordering = if critical { Severity::Error } else { Severity::Warning };
+        match name.as_str() {
+            "IHDR" => {
+                if index != 0 {
+                    self.report(Severity::Error, index, "IHDR must be the first chunk".to_string());
+                }
+                if duplicate {
+                    self.report(Severity::Error, index, "Duplicate IHDR chunk".to_string());
+                }
+                match Ihdr::try_from(chunk) {
+                    Ok(ihdr) if !duplicate => self.ihdr = Some(ihdr),
+                    Ok(_) => {}
+                    Err(e) => self.report(Severity::Error, index, format!("Invalid IHDR: {}", e)),
+                }
+            }
+            "PLTE" => {
+                if duplicate {
+                    self.report(Severity::Error, index, "Duplicate PLTE chunk".to_string());
+                }
+                if self.first_idat.is_some() {
+                    self.report(Severity::Error, index, "PLTE must appear before the first IDAT".to_string());
+                }
+                if self.plte_index.is_none() {
+                    self.plte_index = Some(index);
+                    self.check_palette(index, chunk);
+                }
+            }
+            "IDAT" => {
+                if let Some(last) = self.last_idat {
+                    if last + 1 != index {
+                        self.report(Severity::Error, index, "IDAT chunks must be consecutive".to_string());
+                    }
+                }
+                self.first_idat.get_or_insert(index);
+                self.last_idat = Some(index);
+            }
+            "IEND" => {
+                if !chunk.data().is_empty() {
+                    self.report(Severity::Warning, index, "IEND chunk should be empty".to_string());
+                }
+                self.iend_index.get_or_insert(index);
+            }
+            _ => {
+                if duplicate && UNIQUE_ANCILLARY.contains(&name.as_str()) {
+                    self.report(Severity::Warning, index, format!("Duplicate {} chunk", name));
+                }
+                if BEFORE_PLTE.contains(&name.as_str()) && self.plte_index.is_some() {
+                    self.report(ordering, index, format!("{} must appear before PLTE", name));
+                }
+                if AFTER_PLTE.contains(&name.as_str()) && self.plte_index.is_none() {
+                    let indexed = self.ihdr.is_some_and(|ihdr| ihdr.color_type == ColorType::Indexed);
+                    if self.has_plte || indexed {
+                        self.report(ordering, index, format!("{} must appear after PLTE", name));
+                    } else if name == "hIST" {
+                        self.report(Severity::Warning, index, "hIST requires a preceding PLTE".to_string());
+                    }
+                }
+                let before_idat = BEFORE_PLTE.contains(&name.as_str())
+                    || AFTER_PLTE.contains(&name.as_str())
+                    || BEFORE_IDAT.contains(&name.as_str());
+                if before_idat && self.first_idat.is_some() {
+                    self.report(ordering, index, format!("{} must appear before IDAT", name));
+                }
+            }
+        }
+
+        if matches!(name.as_str(), "acTL" | "fcTL" | "fdAT") {
+            self.check_animation(index, &name, chunk);
+        }
+        if KnownChunk::CHUNK_TYPES.contains(&name.as_str()) {
+            self.check_known(index, chunk);
+        }
+        if name == "tRNS" {
+            self.check_transparency(index, chunk);
+        }
+
+        if !duplicate {
+            self.seen.push(name);
+        }
+        self.offset += 4 + 4 + chunk.length() as u64 + 4;
+    }
+
+    // 检查 PLTE 的长度，以及索引色图像的颜色数是否超出位深度；颜色类型是否允许 PLTE 在 finish 中检查
+    fn check_palette(&mut self, index: usize, chunk: &Chunk) {
+        let palette = match Palette::try_from(chunk) {
+            Ok(palette) => palette,
+            Err(e) => return self.report(Severity::Error, index, e.to_string()),
+        };
+        if let Some(ihdr) = self.ihdr.filter(|ihdr| ihdr.color_type == ColorType::Indexed) {
+            if let Err(e) = palette.validate(&ihdr) {
+                self.report(Severity::Error, index, e.to_string());
+            }
+        }
+        self.palette = Some(palette);
+    }
+
+    // 按颜色类型检查 tRNS 的格式、透明色取值范围以及透明度项数
+    fn check_transparency(&mut self, index: usize, chunk: &Chunk) {
+        let Some(ihdr) = self.ihdr else {
+            return;
+        };
+        let transparency = match Transparency::parse(chunk.data(), &ihdr) {
+            Ok(transparency) => transparency,
+            Err(e) => return self.report(Severity::Warning, index, e.to_string()),
+        };
+        if matches!(transparency, Transparency::Alpha(_)) && self.palette.is_none() {
+            self.pending_transparency = Some((index, transparency));
+            return;
+        }
+        if let Err(e) = transparency.validate(&ihdr, self.palette.as_ref()) {
+            self.report(Severity::Warning, index, e.to_string());
+        }
+    }
+
+    // 检查标准辅助数据块的内容；解码器会忽略无效的辅助数据块，因此只报告警告
+    fn check_known(&mut self, index: usize, chunk: &Chunk) {
+        let known = match KnownChunk::try_from(chunk) {
+            Ok(known) => known,
+            Err(e) => return self.report(Severity::Warning, index, e.to_string()),
+        };
+        if let Some(ihdr) = &self.ihdr {
+            if let Err(e) = known.validate(ihdr) {
+                return self.report(Severity::Warning, index, e.to_string());
+            }
+        }
+        match (&known, self.palette.as_ref().map(|palette| palette.entries.len())) {
+            (KnownChunk::Histogram(frequencies), Some(entries)) if frequencies.len() != entries => self.report(
+                Severity::Warning,
+                index,
+                format!("hIST has {} entries but PLTE has {}", frequencies.len(), entries),
+            ),
+            (KnownChunk::Background(Background::PaletteIndex(i)), Some(entries)) if *i as usize >= entries => self.report(
+                Severity::Warning,
+                index,
+                format!("bKGD palette index {} out of range for {} entries", i, entries),
+            ),
+            _ => {}
+        }
+    }
+
+    // 检查 APNG 数据块：fcTL 与 fdAT 共用一个从 0 开始连续递增的序号
+    fn check_animation(&mut self, index: usize, name: &str, chunk: &Chunk) {
+        let data = chunk.data();
+        if name == "acTL" {
+            match AnimationControl::try_from(chunk) {
+                Ok(animation) => {
+                    self.animation_frames.get_or_insert(animation.num_frames);
+                }
+                Err(e) => self.report(Severity::Error, index, e.to_string()),
+            }
+            return;
+        }
+        if data.len() < 4 {
+            self.report(Severity::Error, index, format!("{} chunk is too short", name));
+            return;
+        }
+        let sequence = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        if sequence != self.next_sequence {
+            self.report(
+                Severity::Error,
+                index,
+                format!("{} sequence number {} out of order, expected {}", name, sequence, self.next_sequence),
+            );
+        }
+        self.next_sequence = sequence.wrapping_add(1);
+        if name == "fcTL" {
+            if let Err(e) = FrameControl::try_from(chunk) {
+                self.report(Severity::Error, index, e.to_string());
+            }
+            self.frame_count += 1;
+        } else if self.frame_count == 0 {
+            self.report(Severity::Error, index, "fdAT chunk without a preceding fcTL".to_string());
+        }
+    }
+
+    // 检查只能在看完全部数据块后判断的规则
+    fn finish(&mut self, chunks: &[Chunk]) {
+        if chunks.is_empty() {
+            self.report_file(Severity::Error, "PNG has no chunks".to_string());
+            return;
+        }
+        if !self.seen.iter().any(|s| s == "IHDR") {
+            self.report_file(Severity::Error, "Missing IHDR chunk".to_string());
+        }
+        if self.first_idat.is_none() {
+            self.report_file(Severity::Error, "Missing IDAT chunk".to_string());
+        }
+        if self.iend_index.is_none() {
+            self.report_file(Severity::Error, "Missing IEND chunk".to_string());
+        }
+        match self.animation_frames {
+            Some(frames) if frames != self.frame_count => self.report_file(
+                Severity::Error,
+                format!("acTL declares {} frame(s) but {} fcTL chunk(s) found", frames, self.frame_count),
+            ),
+            None if self.frame_count > 0 => {
+                self.report_file(Severity::Warning, "fcTL chunks without acTL are ignored".to_string());
+            }
+            _ => {}
+        }
+        if let (Some((index, transparency)), Some(ihdr)) = (self.pending_transparency.take(), self.ihdr) {
+            if let Err(e) = transparency.validate(&ihdr, self.palette.as_ref()) {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    chunk_index: Some(index),
+                    offset: Some(chunk_offset(chunks, index)),
+                    message: e.to_string(),
+                });
+            }
+        }
+        if self.seen.iter().any(|s| s == "sRGB") && self.seen.iter().any(|s| s == "iCCP") {
+            self.report_file(Severity::Warning, "sRGB and iCCP should not both be present".to_string());
+        }
+        if let Ok(ihdr) = Ihdr::try_from(&chunks[0]) {
+            match (ihdr.color_type, self.plte_index) {
+                (ColorType::Indexed, None) => {
+                    self.report_file(Severity::Error, "Indexed-color image requires a PLTE chunk".to_string());
+                }
+                (ColorType::Grayscale | ColorType::GrayscaleAlpha, Some(index)) => {
+                    let offset = chunk_offset(chunks, index);
+                    self.diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        chunk_index: Some(index),
+                        offset: Some(offset),
+                        message: format!("PLTE is not allowed for color type {}", ihdr.color_type),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// 计算第 index 个数据块在文件中的字节偏移
+pub(crate) fn chunk_offset(chunks: &[Chunk], index: usize) -> u64 {
+    8 + chunks[..index].iter().map(|c| 12 + c.length() as u64).sum::<u64>()
+}
+
+// 按 PNG 规范检查数据块类型与排列顺序
+pub(crate) fn validate(chunks: &[Chunk]) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        diagnostics: Vec::new(),
+        offset: 8,
+        seen: Vec::new(),
+        plte_index: None,
+        has_plte: chunks.iter().any(|c| c.chunk_type().to_string() == "PLTE"),
+        ihdr: None,
+        palette: None,
+        pending_transparency: None,
+        first_idat: None,
+        last_idat: None,
+        iend_index: None,
+        animation_frames: None,
+        frame_count: 0,
+        next_sequence: 0,
+    };
+    for (index, chunk) in chunks.iter().enumerate() {
+        validator.check_chunk(index, chunk);
+    }
+    validator.finish(chunks);
+    validator.diagnostics
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use crate::ihdr::InterlaceMethod;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+    }
+
+    fn ihdr(color_type: ColorType) -> Chunk {
+        Ihdr::new(1, 1, 8, color_type, InterlaceMethod::None).unwrap().to_chunk()
+    }
+
+    fn errors(chunks: &[Chunk]) -> Vec<Diagnostic> {
+        validate(chunks).into_iter().filter(|d| d.severity == Severity::Error).collect()
+    }
+
+    #[test]
+    fn test_valid_minimal_png() {
+        let chunks = vec![ihdr(ColorType::Rgb), chunk("IDAT", &[0]), chunk("IEND", &[])];
+        assert!(validate(&chunks).is_empty());
+    }
+
+    #[test]
+    fn test_missing_ihdr() {
+        let chunks = vec![chunk("IDAT", &[0]), chunk("IEND", &[])];
+        let errors = errors(&chunks);
+        assert!(errors.iter().any(|d| d.message.contains("First chunk must be IHDR")));
+        assert!(errors.iter().any(|d| d.message == "Missing IHDR chunk" && d.chunk_index.is_none()));
+    }
+
+    #[test]
+    fn test_chunk_after_iend() {
+        let chunks = vec![ihdr(ColorType::Rgb), chunk("IDAT", &[0]), chunk("IEND", &[]), chunk("ruSt", b"hi")];
+        let errors = errors(&chunks);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].chunk_index, Some(3));
+        assert_eq!(errors[0].offset, Some(8 + 25 + 13 + 12));
+    }
+
+    #[test]
+    fn test_non_consecutive_idat() {
+        let chunks = vec![ihdr(ColorType::Rgb), chunk("IDAT", &[0]), chunk("ruSt", b"hi"), chunk("IDAT", &[0]), chunk("IEND", &[])];
+        let errors = errors(&chunks);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("consecutive"));
+    }
+
+    #[test]
+    fn test_plte_after_idat() {
+        let chunks = vec![ihdr(ColorType::Indexed), chunk("IDAT", &[0]), chunk("PLTE", &[0, 0, 0]), chunk("IEND", &[])];
+        assert!(errors(&chunks).iter().any(|d| d.message.contains("PLTE must appear before")));
+    }
+
+    #[test]
+    fn test_missing_plte_for_indexed() {
+        let chunks = vec![ihdr(ColorType::Indexed), chunk("IDAT", &[0]), chunk("IEND", &[])];
+        assert!(errors(&chunks).iter().any(|d| d.message.contains("requires a PLTE")));
+    }
+
+    #[test]
+    fn test_ancillary_before_plte() {
+        let chunks = vec![
+            ihdr(ColorType::Rgb),
+            chunk("bKGD", &[0, 0, 0, 0, 0, 0]),
+            chunk("PLTE", &[0, 0, 0]),
+            chunk("hIST", &[0, 1]),
+            chunk("IDAT", &[0]),
+            chunk("IEND", &[]),
+        ];
+        let diagnostics = validate(&chunks);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].chunk_index, Some(1));
+        assert_eq!(diagnostics[0].message, "bKGD must appear after PLTE");
+
+        // 索引色图像缺少 PLTE 时同样要求 tRNS 位于 PLTE 之后
+        let chunks = vec![ihdr(ColorType::Indexed), chunk("tRNS", &[0]), chunk("IDAT", &[0]), chunk("IEND", &[])];
+        assert!(validate(&chunks).iter().any(|d| d.message == "tRNS must appear after PLTE"));
+    }
+
+    #[test]
+    fn test_duplicate_unique_chunks() {
+        let chunks = vec![
+            ihdr(ColorType::Rgb),
+            chunk("gAMA", &[0, 0, 177, 143]),
+            chunk("gAMA", &[0, 0, 177, 143]),
+            chunk("IDAT", &[0]),
+            chunk("IEND", &[]),
+            chunk("IEND", &[]),
+        ];
+        let diagnostics = validate(&chunks);
+        assert!(diagnostics.iter().any(|d| d.severity == Severity::Warning && d.message == "Duplicate gAMA chunk"));
+        assert!(diagnostics.iter().any(|d| d.severity == Severity::Error && d.chunk_index == Some(5)));
+    }
+
+    #[test]
+    fn test_invalid_chunk_types() {
+        let chunks = vec![ihdr(ColorType::Rgb), chunk("rust", b""), chunk("ABCD", b""), chunk("IDAT", &[0]), chunk("IEND", &[])];
+        let errors = errors(&chunks);
+        assert!(errors.iter().any(|d| d.message.contains("reserved bit")));
+        assert!(errors.iter().any(|d| d.message.contains("Unknown critical chunk ABCD")));
+    }
+
+    #[test]
+    fn test_animation_sequence_numbers() {
+        let fctl = |sequence: u32| {
+            let mut data = vec![0; 26];
+            data[..4].copy_from_slice(&sequence.to_be_bytes());
+            data[4..12].copy_from_slice(&[0, 0, 0, 1, 0, 0, 0, 1]);
+            chunk("fcTL", &data)
+        };
+        let chunks = vec![
+            ihdr(ColorType::Rgb),
+            chunk("acTL", &[0, 0, 0, 2, 0, 0, 0, 0]),
+            fctl(0),
+            chunk("IDAT", &[0]),
+            fctl(1),
+            chunk("fdAT", &[0, 0, 0, 3, 0]),
+            chunk("IEND", &[]),
+        ];
+        let found = errors(&chunks);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].chunk_index, Some(5));
+        assert_eq!(found[0].message, "fdAT sequence number 3 out of order, expected 2");
+
+        let chunks = vec![ihdr(ColorType::Rgb), chunk("acTL", &[0, 0, 0, 2, 0, 0, 0, 0]), fctl(0), chunk("IDAT", &[0]), chunk("IEND", &[])];
+        assert!(errors(&chunks).iter().any(|d| d.message.contains("declares 2 frame(s) but 1")));
+    }
+
+    #[test]
+    fn test_ancillary_contents() {
+        let chunks = vec![
+            ihdr(ColorType::Indexed),
+            chunk("sRGB", &[9]),
+            chunk("PLTE", &[0; 6]),
+            chunk("bKGD", &[2]),
+            chunk("hIST", &[0, 1, 0, 2, 0, 3]),
+            chunk("IDAT", &[0]),
+            chunk("IEND", &[]),
+        ];
+        let messages: Vec<String> = validate(&chunks)
+            .into_iter()
+            .inspect(|d| assert_eq!(d.severity, Severity::Warning))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Invalid ancillary chunk: Invalid sRGB rendering intent: 9",
+                "bKGD palette index 2 out of range for 2 entries",
+                "hIST has 3 entries but PLTE has 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ancillary_ordering_is_warning() {
+        let chunks = vec![ihdr(ColorType::Rgb), chunk("IDAT", &[0]), chunk("pHYs", &[0; 9]), chunk("IEND", &[])];
+        let diagnostics = validate(&chunks);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_palette_and_transparency() {
+        let chunks = vec![
+            Ihdr::new(1, 1, 1, ColorType::Indexed, InterlaceMethod::None).unwrap().to_chunk(),
+            chunk("PLTE", &[0; 9]),
+            chunk("tRNS", &[0, 0, 0, 0]),
+            chunk("IDAT", &[0]),
+            chunk("IEND", &[]),
+        ];
+        let diagnostics = validate(&chunks);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].message, "Invalid palette: 3 palette entries exceed bit depth 1");
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert!(diagnostics[1].message.contains("tRNS has 4 entries but the palette has only 3"));
+
+        // tRNS 位于 PLTE 之前时，除顺序错误外仍在最后检查透明度项数
+        let chunks = vec![
+            Ihdr::new(1, 1, 8, ColorType::Indexed, InterlaceMethod::None).unwrap().to_chunk(),
+            chunk("tRNS", &[0, 0]),
+            chunk("PLTE", &[0; 3]),
+            chunk("IDAT", &[0]),
+            chunk("IEND", &[]),
+        ];
+        let messages: Vec<String> = validate(&chunks).into_iter().map(|d| d.message).collect();
+        assert_eq!(
+            messages,
+            ["tRNS must appear after PLTE", "Invalid palette: tRNS has 2 entries but the palette has only 1"]
+        );
+
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Rgba, InterlaceMethod::None).unwrap().to_chunk();
+        let chunks = vec![ihdr, chunk("tRNS", &[0, 0]), chunk("IDAT", &[0]), chunk("IEND", &[])];
+        let diagnostics = validate(&chunks);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("tRNS is not allowed for color type"));
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::Write;
+
+use crate::chunk::Chunk;
+use crate::error::Result;
+use crate::png::Png;
+
+// 流式 PNG 写入器：先写入文件头，再把数据块逐个写入目标，不在内存中拼接整个文件
+pub struct PngWriter<W: Write> {
+    writer: W,
+    offset: u64,
+}
+
+impl<W: Write> PngWriter<W> {
+    // 创建写入器并立即写入标准 PNG 文件头
+    pub fn new(mut writer: W) -> Result<PngWriter<W>> {
+        writer.write_all(&Png::STANDARD_HEADER)?;
+        Ok(PngWriter {
+            writer,
+            offset: Png::STANDARD_HEADER.len() as u64,
+        })
+    }
+
+    // 写入一个数据块（长度、类型、数据、CRC）
+    pub fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        chunk.write_to(&mut self.writer)?;
+        self.offset += 4 + 4 + chunk.length() as u64 + 4;
+        Ok(())
+    }
+
+    // 返回已写入的字节数
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    // 刷新缓冲并取回底层的写入目标
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use crate::reader::PngReader;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_writer_writes_header_only() {
+        let writer = PngWriter::new(Vec::new()).unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes, Png::STANDARD_HEADER);
+    }
+
+    #[test]
+    fn test_writer_matches_as_bytes() {
+        let chunks = vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), b"I am the first chunk".to_vec()),
+            Chunk::new(ChunkType::from_str("LASt").unwrap(), b"I am the last chunk".to_vec()),
+        ];
+        let mut writer = PngWriter::new(Vec::new()).unwrap();
+        for chunk in &chunks {
+            writer.write_chunk(chunk).unwrap();
+        }
+        assert_eq!(writer.offset(), 8 + 12 + 20 + 12 + 19);
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes, Png::from_chunks(chunks).as_bytes());
+    }
+
+    #[test]
+    fn test_writer_round_trip() {
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"Rust".to_vec());
+        let mut writer = PngWriter::new(Vec::new()).unwrap();
+        writer.write_chunk(&chunk).unwrap();
+        let bytes = writer.finish().unwrap();
+        let chunks: Vec<Chunk> = PngReader::new(bytes.as_slice()).unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(chunks, vec![chunk]);
+    }
+}